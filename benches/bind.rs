@@ -0,0 +1,93 @@
+//! Bind/unbind throughput, compared against the original design, where every property allocated
+//! its lock in an `Arc<AtomicBool>` and every binding cloned that `Arc`.
+//!
+//! Run with `cargo bench --bench bind`.
+
+use std::cell::UnsafeCell;
+use std::hint::black_box;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+
+/// Minimal copy of the original `Property`, with the lock behind an `Arc`.
+mod baseline {
+    use super::*;
+
+    pub struct Property<T> {
+        value: UnsafeCell<T>,
+        lock: Arc<AtomicBool>,
+    }
+
+    pub struct Binding<'a, T> {
+        value: &'a mut T,
+        lock: Arc<AtomicBool>,
+    }
+
+    impl<T> Property<T> {
+        pub fn new(value: T) -> Self {
+            Property { value: UnsafeCell::new(value), lock: Arc::new(AtomicBool::new(false)) }
+        }
+
+        pub fn bind(&self) -> Binding<'_, T> {
+            if self.lock.swap(true, Ordering::SeqCst) {
+                panic!("already bound");
+            }
+            Binding { value: unsafe { &mut *self.value.get() }, lock: self.lock.clone() }
+        }
+    }
+
+    impl<'a, T> std::ops::Deref for Binding<'a, T> {
+        type Target = T;
+        fn deref(&self) -> &T { self.value }
+    }
+
+    impl<'a, T> std::ops::DerefMut for Binding<'a, T> {
+        fn deref_mut(&mut self) -> &mut T { self.value }
+    }
+
+    impl<'a, T> Drop for Binding<'a, T> {
+        fn drop(&mut self) {
+            self.lock.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+const PROPERTIES: usize = 10_000;
+const ROUNDS: usize = 200;
+
+/// Runs `f` over every property `ROUNDS` times and returns the time per call in nanoseconds.
+fn measure<P>(properties: &[P], f: impl Fn(&P)) -> f64 {
+    // warm up
+    properties.iter().for_each(&f);
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        properties.iter().for_each(&f);
+    }
+    start.elapsed().as_secs_f64() * 1e9 / (ROUNDS * PROPERTIES) as f64
+}
+
+fn report(name: &str, baseline: f64, inline: f64) {
+    println!("{:<24} baseline {:>7.2} ns   inline {:>7.2} ns   ({:.2}x)", name, baseline, inline, baseline / inline);
+}
+
+fn main() {
+    let old: Vec<baseline::Property<f32>> = (0..PROPERTIES).map(|i| baseline::Property::new(i as f32)).collect();
+    let new: Vec<binder::Property<f32>> = (0..PROPERTIES).map(|i| binder::Property::new(i as f32)).collect();
+
+    println!("{} properties, {} rounds", PROPERTIES, ROUNDS);
+    // the Arc's allocation holds the two reference counts next to the flag
+    println!("baseline: {} B per property, plus an allocation of {} B",
+             std::mem::size_of::<baseline::Property<f32>>(), std::mem::size_of::<(usize, usize, AtomicBool)>());
+    println!("inline:   {} B per property, no allocation", std::mem::size_of::<binder::Property<f32>>());
+
+    report("bind + read + unbind",
+           measure(&old, |p| { black_box(*p.bind()); }),
+           measure(&new, |p| { black_box(*p.bind()); }));
+    report("bind + write + unbind",
+           measure(&old, |p| { *p.bind() += 1.0; }),
+           measure(&new, |p| { *p.bind() += 1.0; }));
+    report("create",
+           measure(&old, |p| { black_box(baseline::Property::new(*p.bind())); }),
+           measure(&new, |p| { black_box(binder::Property::new(*p.bind())); }));
+}
@@ -0,0 +1,252 @@
+//! `#[derive(Properties)]` for [binder](https://docs.rs/binder). Use it through binder's `derive`
+//! feature rather than depending on this crate directly. See `binder::Properties` for details.
+
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use std::collections::HashSet;
+
+use proc_macro2::TokenTree;
+use quote::ToTokens;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, ExprRange, Fields, GenericArgument,
+          GenericParam, Generics, Ident, Lit, Meta, MetaNameValue, PathArguments, RangeLimits, Type, Visibility};
+
+
+/// A `Property<T>` field of the struct being derived.
+struct Field {
+    ident: Ident,
+    vis: Visibility,
+    /// The `T` in `Property<T>`.
+    ty: Type,
+    doc: String,
+    range: Option<(Expr, Expr)>,
+}
+
+/// Implements `binder::Properties` for a struct, and generates its `{Name}Snapshot` type. With
+/// `#[properties(reflect)]` on the struct, it also implements `binder::Reflect`.
+#[proc_macro_derive(Properties, attributes(properties, property))]
+pub fn derive_properties(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(syn::Error::new_spanned(&input.ident, "Properties can only be derived for structs with named fields"))
+        },
+        _ => return Err(syn::Error::new_spanned(&input.ident, "Properties can only be derived for structs"))
+    };
+
+    let reflect = reflect(&input.attrs)?;
+    let mut properties = Vec::new();
+    for field in fields {
+        let range = range(&field.attrs)?;
+        match property_type(&field.ty) {
+            Some(ty) => properties.push(Field {
+                ident: field.ident.clone().unwrap(),
+                vis: field.vis.clone(),
+                ty: ty.clone(),
+                doc: doc(&field.attrs),
+                range,
+            }),
+            None => {
+                if let Some(attr) = field.attrs.iter().find(|a| a.path().is_ident("property")) {
+                    return Err(syn::Error::new_spanned(attr, "`#[property]` can only be used on fields of type `Property<T>`"));
+                }
+            }
+        }
+    }
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let snapshot = format_ident!("{}Snapshot", name);
+    let snapshot_doc = format!("Plain-data copy of the properties of [`{}`].", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let snapshot_generics = snapshot_generics(&input.generics, &properties);
+    let (_, snapshot_ty_generics, snapshot_where_clause) = snapshot_generics.split_for_impl();
+
+    let idents: Vec<&Ident> = properties.iter().map(|f| &f.ident).collect();
+    let vises = properties.iter().map(|f| &f.vis);
+    let types = properties.iter().map(|f| &f.ty);
+    let infos = properties.iter().map(|f| {
+        let name = f.ident.to_string();
+        let doc = &f.doc;
+        let range = match &f.range {
+            Some((min, max)) => quote!(::std::option::Option::Some(((#min) as f64, (#max) as f64))),
+            None => quote!(::std::option::Option::None),
+        };
+        quote!(::binder::FieldInfo::new(#name, #doc, #range))
+    });
+    let indices: Vec<usize> = (0..properties.len()).collect();
+
+    let reflect = reflect.then(|| quote! {
+        impl #impl_generics ::binder::Reflect for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn visit<'__a>(&'__a self, visitor: &mut dyn ::binder::PropertyVisitor<'__a>) {
+                #(visitor.visit(&<Self as ::binder::Properties>::FIELDS[#indices], &self.#idents);)*
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[doc = #snapshot_doc]
+        #[derive(Clone)]
+        #vis struct #snapshot #snapshot_generics #snapshot_where_clause {
+            #(#vises #idents: #types,)*
+        }
+
+        impl #impl_generics ::binder::Properties for #name #ty_generics #where_clause {
+            type Snapshot = #snapshot #snapshot_ty_generics;
+
+            const FIELDS: &'static [::binder::FieldInfo] = &[#(#infos),*];
+
+            fn snapshot(&self) -> Self::Snapshot {
+                #snapshot {
+                    #(#idents: ::std::clone::Clone::clone(&*self.#idents.bind_ref()),)*
+                }
+            }
+
+            fn restore(&self, snapshot: Self::Snapshot) {
+                let #snapshot { #(#idents),* } = snapshot;
+                #(*self.#idents.bind() = #idents;)*
+            }
+
+            #[allow(unused_variables)]
+            fn visit_fields<'__a, V: ::binder::FieldVisitor<'__a>>(&'__a self, visitor: &mut V) {
+                #(visitor.visit(&Self::FIELDS[#indices], &self.#idents);)*
+            }
+        }
+
+        #reflect
+    })
+}
+
+/// Returns `T` if the type is `Property<T>`, with or without a path in front.
+fn property_type(ty: &Type) -> Option<&Type> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Property" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// The generics of the snapshot struct: only the parameters that the properties' value types
+/// use, since the snapshot has no field for the rest, and only the where clauses about those.
+fn snapshot_generics(generics: &Generics, properties: &[Field]) -> Generics {
+    let mut used = HashSet::new();
+    for field in properties {
+        names(field.ty.to_token_stream(), &mut used);
+    }
+
+    let mut snapshot = generics.clone();
+    snapshot.params = generics.params.iter()
+        .filter(|param| used.contains(&param_name(param)))
+        .cloned()
+        .collect();
+    if let Some(where_clause) = &mut snapshot.where_clause {
+        let params: HashSet<String> = generics.params.iter().map(param_name).collect();
+        where_clause.predicates = where_clause.predicates.iter()
+            .filter(|predicate| {
+                let mut names_in_predicate = HashSet::new();
+                names(predicate.to_token_stream(), &mut names_in_predicate);
+                names_in_predicate.iter().all(|name| !params.contains(name) || used.contains(name))
+            })
+            .cloned()
+            .collect();
+    }
+    snapshot
+}
+
+/// The name a generic parameter is referred to by, e.g. `T` or `'a`.
+fn param_name(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Type(param) => param.ident.to_string(),
+        GenericParam::Lifetime(param) => param.lifetime.to_string(),
+        GenericParam::Const(param) => param.ident.to_string(),
+    }
+}
+
+/// Collects every identifier and lifetime in the tokens, which is a superset of the generic
+/// parameters they use.
+fn names(tokens: TokenStream2, names_found: &mut HashSet<String>) {
+    let mut tokens = tokens.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Ident(ident) => { names_found.insert(ident.to_string()); }
+            TokenTree::Group(group) => names(group.stream(), names_found),
+            TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                if let Some(TokenTree::Ident(ident)) = tokens.next() {
+                    names_found.insert(format!("'{}", ident));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Joins the lines of a doc comment.
+fn doc(attrs: &[Attribute]) -> String {
+    let lines: Vec<String> = attrs.iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(MetaNameValue { value: Expr::Lit(ExprLit { lit: Lit::Str(s), .. }), .. }) => Some(s.value()),
+            _ => None
+        })
+        .map(|line| line.trim().to_string())
+        .collect();
+    lines.join("\n")
+}
+
+/// Parses `#[properties(reflect)]` on the struct.
+fn reflect(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut reflect = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("properties")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("reflect") {
+                return Err(meta.error("unknown properties attribute, expected `reflect`"));
+            }
+            if reflect {
+                return Err(meta.error("duplicate `reflect` attribute"));
+            }
+            reflect = true;
+            Ok(())
+        })?;
+    }
+    Ok(reflect)
+}
+
+/// Parses `#[property(range = min..=max)]`.
+fn range(attrs: &[Attribute]) -> syn::Result<Option<(Expr, Expr)>> {
+    let mut range = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("property")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("range") {
+                return Err(meta.error("unknown property attribute, expected `range`"));
+            }
+            if range.is_some() {
+                return Err(meta.error("duplicate `range` attribute"));
+            }
+            match meta.value()?.parse::<Expr>()? {
+                Expr::Range(ExprRange { start: Some(min), limits: RangeLimits::Closed(_), end: Some(max), .. }) => {
+                    range = Some((*min, *max));
+                    Ok(())
+                }
+                other => Err(syn::Error::new_spanned(other, "expected an inclusive range, like `0.0..=1.0`"))
+            }
+        })?;
+    }
+    Ok(range)
+}
@@ -0,0 +1,144 @@
+use binder::{FieldInfo, FieldVisitor, Properties, Property};
+
+
+#[derive(Properties)]
+#[properties(reflect)]
+pub struct Transform {
+    /// Position along the x axis.
+    #[property(range = -10.0..=10.0)]
+    pub x: Property<f32>,
+    /// Number of copies.
+    /// At least one.
+    #[property(range = 1..=8)]
+    pub copies: binder::Property<u8>,
+    pub name: Property<String>,
+    pub id: u32,
+}
+
+#[derive(Properties)]
+#[properties(reflect)]
+struct Wrapper<T: Clone + 'static> {
+    value: Property<T>,
+}
+
+/// `U` and `'a` aren't used by any property, so the snapshot doesn't get them.
+#[derive(Properties)]
+struct Tagged<'a, T: Clone + 'static, U> where U: Default {
+    value: Property<T>,
+    tag: std::marker::PhantomData<&'a U>,
+}
+
+/// Value types only need to be `Clone`.
+#[derive(Clone, PartialEq)]
+struct Opaque(u8);
+
+#[derive(Properties)]
+struct Holder {
+    opaque: Property<Opaque>,
+}
+
+fn transform() -> Transform {
+    Transform {
+        x: Property::new(1.0),
+        copies: Property::new(2),
+        name: Property::new(String::from("box")),
+        id: 7,
+    }
+}
+
+#[test]
+fn field_info() {
+    let names: Vec<&str> = Transform::FIELDS.iter().map(FieldInfo::name).collect();
+    assert_eq!(names, ["x", "copies", "name"]);
+    assert_eq!(Transform::FIELDS[0].doc(), "Position along the x axis.");
+    assert_eq!(Transform::FIELDS[0].range(), Some(-10.0..=10.0));
+    assert_eq!(Transform::FIELDS[1].doc(), "Number of copies.\nAt least one.");
+    assert_eq!(Transform::FIELDS[1].range(), Some(1.0..=8.0));
+    assert_eq!(Transform::FIELDS[2].doc(), "");
+    assert_eq!(Transform::FIELDS[2].range(), None);
+}
+
+#[test]
+fn snapshot_restore() {
+    let t = transform();
+    let snapshot: TransformSnapshot = t.snapshot();
+    *t.x.bind() = 5.0;
+    t.name.bind().push_str("es");
+    assert_eq!(snapshot.name, "box");
+    t.restore(snapshot.clone());
+    assert_eq!((*t.x.bind_ref(), t.name.bind_ref().as_str()), (1.0, "box"));
+
+    let w = Wrapper { value: Property::new(vec![1, 2]) };
+    let snapshot: WrapperSnapshot<Vec<i32>> = w.snapshot();
+    w.value.bind().clear();
+    w.restore(snapshot);
+    assert_eq!(*w.value.bind_ref(), [1, 2]);
+
+    let t = Tagged::<_, ()> { value: Property::new(1), tag: std::marker::PhantomData };
+    let snapshot: TaggedSnapshot<i32> = t.snapshot();
+    *t.value.bind() = 2;
+    t.restore(snapshot);
+    assert_eq!(*t.value.bind_ref(), 1);
+
+    let h = Holder { opaque: Property::new(Opaque(1)) };
+    let snapshot = h.snapshot();
+    *h.opaque.bind() = Opaque(2);
+    h.restore(snapshot);
+    assert!(*h.opaque.bind_ref() == Opaque(1));
+}
+
+#[test]
+fn visit_fields() {
+    struct Describe(Vec<String>);
+
+    impl<'a> FieldVisitor<'a> for Describe {
+        fn visit<T: 'static>(&mut self, info: &'static FieldInfo, property: &'a Property<T>) {
+            let value = property.bind_ref();
+            let any: &dyn std::any::Any = &*value;
+            let value = if let Some(v) = any.downcast_ref::<f32>() { v.to_string() }
+                else if let Some(v) = any.downcast_ref::<u8>() { v.to_string() }
+                else if let Some(v) = any.downcast_ref::<String>() { v.clone() }
+                else { String::from("?") };
+            self.0.push(format!("{} = {}", info.name(), value));
+        }
+    }
+
+    let mut describe = Describe(Vec::new());
+    transform().visit_fields(&mut describe);
+    assert_eq!(describe.0, ["x = 1", "copies = 2", "name = box"]);
+
+    struct Collect<'a>(Vec<&'a Property<f32>>);
+
+    impl<'a> FieldVisitor<'a> for Collect<'a> {
+        fn visit<T: 'static>(&mut self, _: &'static FieldInfo, property: &'a Property<T>) {
+            if let Some(property) = (property as &dyn std::any::Any).downcast_ref::<Property<f32>>() {
+                self.0.push(property);
+            }
+        }
+    }
+
+    let t = transform();
+    let mut collect = Collect(Vec::new());
+    t.visit_fields(&mut collect);
+    assert_eq!(collect.0.len(), 1);
+    *collect.0[0].bind() = 3.0;
+    assert_eq!(*t.x.bind_ref(), 3.0);
+}
+
+#[test]
+fn derive_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
+
+#[test]
+fn reflect() {
+    use binder::{AnyProperty, Reflect};
+    let t = transform();
+    *t.property("copies").unwrap().bind_any().downcast_mut::<u8>().unwrap() = 4;
+    assert_eq!(*t.copies.bind_ref(), 4);
+    assert_eq!(t.property("name").map(AnyProperty::type_name), Some(std::any::type_name::<String>()));
+    assert!(t.property("id").is_none());
+
+    let w = Wrapper { value: Property::new(1u8) };
+    assert_eq!(w.property("value").map(AnyProperty::type_name), Some("u8"));
+}
@@ -0,0 +1,9 @@
+use binder::{Properties, Property};
+
+#[derive(Properties)]
+struct Light {
+    #[property(range = 0.0..=1.0, range = 0.0..=2.0)]
+    intensity: Property<f32>,
+}
+
+fn main() {}
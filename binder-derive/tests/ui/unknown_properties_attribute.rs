@@ -0,0 +1,9 @@
+use binder::{Properties, Property};
+
+#[derive(Properties)]
+#[properties(reflection)]
+struct Light {
+    intensity: Property<f32>,
+}
+
+fn main() {}
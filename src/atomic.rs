@@ -0,0 +1,336 @@
+//! Lock-free properties for small `Copy` values.
+
+
+use std::any::TypeId;
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::mem::{align_of, size_of, transmute_copy, MaybeUninit};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{fence, AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
+
+
+/// An atomic integer that can hold the bits of a value of the same size.
+trait Word {
+    type Int: Copy + Eq;
+    fn load(&self) -> Self::Int;
+    fn store(&self, value: Self::Int);
+    fn compare_exchange(&self, current: Self::Int, new: Self::Int) -> Result<Self::Int, Self::Int>;
+}
+
+macro_rules! impl_word {
+    ($($A:ty => $I:ty),+) => {$(
+        impl Word for $A {
+            type Int = $I;
+
+            fn load(&self) -> $I {
+                <$A>::load(self, Ordering::Acquire)
+            }
+
+            fn store(&self, value: $I) {
+                <$A>::store(self, value, Ordering::Release)
+            }
+
+            fn compare_exchange(&self, current: $I, new: $I) -> Result<$I, $I> {
+                <$A>::compare_exchange(self, current, new, Ordering::AcqRel, Ordering::Acquire)
+            }
+        }
+    )+};
+}
+
+impl_word!(AtomicU8 => u8, AtomicU16 => u16, AtomicU32 => u32, AtomicU64 => u64);
+
+/// How a value of type `T` is stored.
+#[derive(Clone, Copy)]
+enum Repr {
+    U8,
+    U16,
+    U32,
+    U64,
+    SeqLock,
+}
+
+/// Only primitives are stored in atomics, since other types may have padding bytes, which can't
+/// be read as part of an integer.
+fn repr<T: 'static>() -> Repr {
+    let primitives = [
+        TypeId::of::<bool>(), TypeId::of::<char>(), TypeId::of::<f32>(), TypeId::of::<f64>(),
+        TypeId::of::<u8>(), TypeId::of::<u16>(), TypeId::of::<u32>(), TypeId::of::<u64>(), TypeId::of::<usize>(),
+        TypeId::of::<i8>(), TypeId::of::<i16>(), TypeId::of::<i32>(), TypeId::of::<i64>(), TypeId::of::<isize>(),
+    ];
+    if !primitives.contains(&TypeId::of::<T>()) {
+        return Repr::SeqLock;
+    }
+    // the atomics may need stricter alignment than the primitive, e.g. `u64` on 32-bit x86
+    match (size_of::<T>(), align_of::<T>()) {
+        (1, align) if align >= align_of::<AtomicU8>() => Repr::U8,
+        (2, align) if align >= align_of::<AtomicU16>() => Repr::U16,
+        (4, align) if align >= align_of::<AtomicU32>() => Repr::U32,
+        (8, align) if align >= align_of::<AtomicU64>() => Repr::U64,
+        _ => Repr::SeqLock,
+    }
+}
+
+/// A property for small [Copy] values like `f32`, `bool` or `u32`, which are read and written
+/// whole instead of being bound. Primitives are stored in a std atomic of the same size, so
+/// they're lock-free. Other types are protected by a seqlock: readers retry if a write happened
+/// while they were copying the value, and writers spin while another write is in progress, so
+/// both can spin for as long as a writing thread is preempted. Writes are only a copy, though,
+/// and no user code runs while the seqlock is held.
+///
+/// There are no hooks, so unlike [Property](crate::Property) there's no way to observe or
+/// validate changes. Widgets that need a `&mut T` can use [bind](AtomicProperty::bind), which
+/// works on a copy of the value and writes it back when dropped.
+///
+/// ```rust
+/// let volume = binder::AtomicProperty::new(0.5f32);
+/// volume.set(0.75);
+/// volume.update(|v| *v = v.min(0.6));
+/// assert_eq!(volume.get(), 0.6);
+/// assert_eq!(volume.compare_exchange(0.6, 1.0), Ok(0.6));
+/// *volume.bind() -= 0.5;
+/// assert_eq!(volume.get(), 0.5);
+/// ```
+pub struct AtomicProperty<T> {
+    value: UnsafeCell<T>,
+    /// Sequence number for the seqlock, odd while a write is in progress. Unused for primitives.
+    seq: AtomicUsize,
+}
+
+impl<T: Copy + 'static> AtomicProperty<T> {
+    /// Creates a new `AtomicProperty` that holds the given value.
+    pub const fn new(value: T) -> Self {
+        AtomicProperty { value: UnsafeCell::new(value), seq: AtomicUsize::new(0) }
+    }
+
+    /// Views the value as an atomic integer. Only valid if `repr` returned the matching size.
+    fn word<A: Word>(&self) -> &A {
+        // SAFETY: `repr` checked that the size and alignment match, and the value is only ever
+        // accessed through the atomic while it's shared
+        unsafe { &*(self.value.get() as *const A) }
+    }
+
+    fn load_word<A: Word>(&self) -> T {
+        let bits = self.word::<A>().load();
+        unsafe { transmute_copy(&bits) }
+    }
+
+    fn store_word<A: Word>(&self, value: T) {
+        self.word::<A>().store(unsafe { transmute_copy(&value) });
+    }
+
+    /// Bitwise compare-and-swap. Returns the value that was found if it's not `current`.
+    fn compare_exchange_word<A: Word>(&self, current: T, new: T) -> Result<(), T> {
+        let (current, new) = unsafe { (transmute_copy(&current), transmute_copy(&new)) };
+        self.word::<A>().compare_exchange(current, new).map(|_| ()).map_err(|bits| unsafe { transmute_copy(&bits) })
+    }
+
+    /// Locks out other writers, returning the (even) sequence number from before. Spins while
+    /// another write is in progress.
+    fn write_lock(&self) -> usize {
+        loop {
+            let seq = self.seq.load(Ordering::Relaxed);
+            if seq & 1 == 0 && self.seq.compare_exchange_weak(seq, seq.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed).is_ok() {
+                return seq;
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Reads the value, along with the (even) sequence number it was read at.
+    fn read_seq(&self) -> (T, usize) {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before & 1 == 0 {
+                // SAFETY: the copy may be torn if a write happens meanwhile, but then the sequence
+                // number changes and it's thrown away without being used. It's kept in a
+                // `MaybeUninit` until it's known to be valid.
+                let value = unsafe { std::ptr::read_volatile(self.value.get() as *const MaybeUninit<T>) };
+                fence(Ordering::Acquire);
+                if self.seq.load(Ordering::Relaxed) == before {
+                    return (unsafe { value.assume_init() }, before);
+                }
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Writes the value while holding the seqlock's write lock, which was taken at `seq`.
+    fn write_locked(&self, seq: usize, value: T) {
+        // readers that see any of the following writes also see the odd sequence number
+        fence(Ordering::Release);
+        // SAFETY: other writers are locked out, and readers check for concurrent writes
+        unsafe { std::ptr::write_volatile(self.value.get(), value) };
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    /// Writes the value if nothing was written since `seq` was read by `read_seq`. Returns
+    /// `false` if something was.
+    fn write_seq_if(&self, seq: usize, value: T) -> bool {
+        if self.seq.compare_exchange(seq, seq.wrapping_add(1), Ordering::Acquire, Ordering::Relaxed).is_err() {
+            return false;
+        }
+        self.write_locked(seq, value);
+        true
+    }
+
+    /// Returns a copy of the value.
+    pub fn get(&self) -> T {
+        match repr::<T>() {
+            Repr::U8 => self.load_word::<AtomicU8>(),
+            Repr::U16 => self.load_word::<AtomicU16>(),
+            Repr::U32 => self.load_word::<AtomicU32>(),
+            Repr::U64 => self.load_word::<AtomicU64>(),
+            Repr::SeqLock => self.read_seq().0,
+        }
+    }
+
+    /// Replaces the value.
+    pub fn set(&self, value: T) {
+        match repr::<T>() {
+            Repr::U8 => self.store_word::<AtomicU8>(value),
+            Repr::U16 => self.store_word::<AtomicU16>(value),
+            Repr::U32 => self.store_word::<AtomicU32>(value),
+            Repr::U64 => self.store_word::<AtomicU64>(value),
+            Repr::SeqLock => self.write_locked(self.write_lock(), value),
+        }
+    }
+
+    /// Bitwise compare-and-swap on the atomic. Not used for seqlock values.
+    fn compare_exchange_bits(&self, current: T, new: T) -> Result<(), T> {
+        match repr::<T>() {
+            Repr::U8 => self.compare_exchange_word::<AtomicU8>(current, new),
+            Repr::U16 => self.compare_exchange_word::<AtomicU16>(current, new),
+            Repr::U32 => self.compare_exchange_word::<AtomicU32>(current, new),
+            Repr::U64 => self.compare_exchange_word::<AtomicU64>(current, new),
+            Repr::SeqLock => unreachable!(),
+        }
+    }
+
+    /// Changes the value with a closure, atomically, and returns the new value. The closure is
+    /// called on a copy of the value, and called again with the latest value if another thread
+    /// changed it in the meantime, so it may be called more than once. It isn't called while any
+    /// lock is held, so it can read the property itself.
+    pub fn update<F: FnMut(&mut T)>(&self, mut f: F) -> T {
+        if let Repr::SeqLock = repr::<T>() {
+            loop {
+                let (mut value, seq) = self.read_seq();
+                f(&mut value);
+                if self.write_seq_if(seq, value) {
+                    return value;
+                }
+            }
+        }
+        let mut current = self.get();
+        loop {
+            let mut new = current;
+            f(&mut new);
+            match self.compare_exchange_bits(current, new) {
+                Ok(()) => return new,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+impl<T: Copy + PartialEq + 'static> AtomicProperty<T> {
+    /// Sets the value to `new` if it's equal to `current`, as compared by [PartialEq]. Returns
+    /// the previous value, as `Ok` if it was replaced and as `Err` if it wasn't.
+    pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
+        if let Repr::SeqLock = repr::<T>() {
+            loop {
+                let (found, seq) = self.read_seq();
+                if found != current {
+                    return Err(found);
+                }
+                if self.write_seq_if(seq, new) {
+                    return Ok(found);
+                }
+            }
+        }
+        let mut found = self.get();
+        loop {
+            if found != current {
+                return Err(found);
+            }
+            // swaps out exactly the bits that were compared, so a concurrent change to a
+            // different but equal value (e.g. from `0.0` to `-0.0`) isn't overwritten
+            match self.compare_exchange_bits(found, new) {
+                Ok(()) => return Ok(found),
+                Err(actual) => found = actual,
+            }
+        }
+    }
+}
+
+impl<T> AtomicProperty<T> {
+    /// Returns a mutable reference to the value. No synchronization is needed, since the borrow
+    /// checker guarantees exclusive access.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    /// Consumes the property and returns the value.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T: Copy + 'static> AtomicProperty<T> {
+    /// Returns a binding that [Deref](std::ops::Deref)s to a copy of the value, so it can be
+    /// passed to code that needs a `&mut T`, like a [PropertyBinding](crate::PropertyBinding).
+    /// If it was mutably dereferenced, the copy is written back with [set](AtomicProperty::set)
+    /// when the binding is dropped.
+    ///
+    /// The property isn't locked while it's bound, so changes made in the meantime by others
+    /// are overwritten when the binding is dropped. Use [update](AtomicProperty::update) for
+    /// read-modify-write changes that must not be lost.
+    pub fn bind(&self) -> AtomicPropertyBinding<'_, T> {
+        AtomicPropertyBinding { property: self, value: self.get(), dirty: false }
+    }
+}
+
+impl<T: Copy + fmt::Debug + 'static> fmt::Debug for AtomicProperty<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicProperty").field("value", &self.get()).finish()
+    }
+}
+
+// values are only ever copied in and out, never shared by reference
+unsafe impl<T: Send> Sync for AtomicProperty<T> {}
+
+/// A copy of an [AtomicProperty]'s value, returned by [bind()](AtomicProperty::bind), that's
+/// written back when it's [Drop](std::ops::Drop)ped if it was mutably dereferenced.
+pub struct AtomicPropertyBinding<'a, T: Copy + 'static> {
+    property: &'a AtomicProperty<T>,
+    value: T,
+    dirty: bool,
+}
+
+impl<'a, T: Copy + 'static> Deref for AtomicPropertyBinding<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'a, T: Copy + 'static> DerefMut for AtomicPropertyBinding<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.dirty = true;
+        &mut self.value
+    }
+}
+
+impl<'a, T: Copy + 'static> Drop for AtomicPropertyBinding<'a, T> {
+    fn drop(&mut self) {
+        if self.dirty {
+            self.property.set(self.value);
+        }
+    }
+}
+
+impl<'a, T: Copy + fmt::Debug + 'static> fmt::Debug for AtomicPropertyBinding<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicPropertyBinding").field("value", &self.value).field("dirty", &self.dirty).finish()
+    }
+}
@@ -0,0 +1,102 @@
+//! Binding different kinds of properties through the same interface.
+
+
+use std::ops::{Deref, DerefMut};
+
+use crate::{BindError, LocalProperty, LocalPropertyBinding, LocalPropertyReadBinding, Property, PropertyBinding,
+            PropertyReadBinding};
+
+
+/// Something that can be bound like a [Property]. Implemented by [Property] and [LocalProperty],
+/// so helpers like UI widgets can accept either one. The methods work exactly like the inherent
+/// ones of the same name.
+///
+/// ```rust
+/// use binder::{Bindable, LocalProperty, Property};
+///
+/// fn nudge(p: &impl Bindable<f32>) {
+///     *p.bind() += 0.1;
+/// }
+///
+/// let shared = Property::new(0.5f32);
+/// let local = LocalProperty::new(1.0f32);
+/// nudge(&shared);
+/// nudge(&local);
+/// assert_eq!((*shared.bind_ref(), *local.bind_ref()), (0.6, 1.1));
+/// ```
+pub trait Bindable<T> {
+    /// The mutable binding returned by [bind](Bindable::bind).
+    type Binding<'a>: DerefMut<Target = T> where Self: 'a;
+    /// The read-only binding returned by [bind_ref](Bindable::bind_ref).
+    type ReadBinding<'a>: Deref<Target = T> where Self: 'a;
+
+    /// Binds the property mutably.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is already bound, or if it's poisoned.
+    fn bind(&self) -> Self::Binding<'_>;
+
+    /// Binds the property mutably, or returns why it can't be bound.
+    fn try_bind(&self) -> Result<Self::Binding<'_>, BindError>;
+
+    /// Binds the property for reading only.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is bound mutably, or if it's poisoned.
+    fn bind_ref(&self) -> Self::ReadBinding<'_>;
+
+    /// Binds the property for reading only, or returns why it can't be bound.
+    fn try_bind_ref(&self) -> Result<Self::ReadBinding<'_>, BindError>;
+}
+
+impl<T> Bindable<T> for Property<T> {
+    type Binding<'a> = PropertyBinding<'a, T> where T: 'a;
+    type ReadBinding<'a> = PropertyReadBinding<'a, T> where T: 'a;
+
+    #[track_caller]
+    fn bind(&self) -> Self::Binding<'_> {
+        Property::bind(self)
+    }
+
+    #[track_caller]
+    fn try_bind(&self) -> Result<Self::Binding<'_>, BindError> {
+        Property::try_bind(self)
+    }
+
+    #[track_caller]
+    fn bind_ref(&self) -> Self::ReadBinding<'_> {
+        Property::bind_ref(self)
+    }
+
+    #[track_caller]
+    fn try_bind_ref(&self) -> Result<Self::ReadBinding<'_>, BindError> {
+        Property::try_bind_ref(self)
+    }
+}
+
+impl<T> Bindable<T> for LocalProperty<T> {
+    type Binding<'a> = LocalPropertyBinding<'a, T> where T: 'a;
+    type ReadBinding<'a> = LocalPropertyReadBinding<'a, T> where T: 'a;
+
+    #[track_caller]
+    fn bind(&self) -> Self::Binding<'_> {
+        LocalProperty::bind(self)
+    }
+
+    #[track_caller]
+    fn try_bind(&self) -> Result<Self::Binding<'_>, BindError> {
+        LocalProperty::try_bind(self)
+    }
+
+    #[track_caller]
+    fn bind_ref(&self) -> Self::ReadBinding<'_> {
+        LocalProperty::bind_ref(self)
+    }
+
+    #[track_caller]
+    fn try_bind_ref(&self) -> Result<Self::ReadBinding<'_>, BindError> {
+        LocalProperty::try_bind_ref(self)
+    }
+}
@@ -0,0 +1,202 @@
+//! Derived values computed from other properties.
+
+
+use std::fmt;
+use std::panic::{AssertUnwindSafe, Location};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::{bind_panic, BindError, Property, PropertyReadBinding, Subscription};
+
+
+/// One or more `&Property` sources for a [Computed] value, and the closure that computes it:
+/// a single `&Property<A>` with `Fn(&A) -> T`, or a tuple of up to six with `Fn(&A, &B, ...) -> T`.
+/// The methods are implementation details.
+pub trait Sources<'a, F, T> {
+    #[doc(hidden)]
+    fn compute(&self, f: &F) -> Result<T, BindError>;
+    #[doc(hidden)]
+    fn watch(&self, dirty: &Arc<AtomicBool>) -> Vec<Subscription>;
+}
+
+/// Commit observer for a source, which marks the computed value as out of date.
+fn mark_dirty<A>(dirty: &Arc<AtomicBool>) -> Arc<dyn Fn(&A) + Send + Sync> {
+    let dirty = dirty.clone();
+    Arc::new(move |_| dirty.store(true, Ordering::Release))
+}
+
+impl<'a, A: 'static, F, T> Sources<'a, F, T> for &'a Property<A>
+    where F: Fn(&A) -> T
+{
+    fn compute(&self, f: &F) -> Result<T, BindError> {
+        Ok(f(&*self.try_bind_ref()?))
+    }
+
+    fn watch(&self, dirty: &Arc<AtomicBool>) -> Vec<Subscription> {
+        vec![self.hooks.watch(mark_dirty(dirty))]
+    }
+}
+
+macro_rules! impl_sources {
+    ($(($A:ident, $i:tt)),+) => {
+        impl<'a, $($A: 'static,)+ Func, T> Sources<'a, Func, T> for ($(&'a Property<$A>,)+)
+            where Func: Fn($(&$A),+) -> T
+        {
+            fn compute(&self, f: &Func) -> Result<T, BindError> {
+                Ok(f($(&*self.$i.try_bind_ref()?),+))
+            }
+
+            fn watch(&self, dirty: &Arc<AtomicBool>) -> Vec<Subscription> {
+                vec![$(self.$i.hooks.watch(mark_dirty(dirty))),+]
+            }
+        }
+    };
+}
+
+impl_sources!((A, 0));
+impl_sources!((A, 0), (B, 1));
+impl_sources!((A, 0), (B, 1), (C, 2));
+impl_sources!((A, 0), (B, 1), (C, 2), (D, 3));
+impl_sources!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4));
+impl_sources!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5));
+
+/// A value derived from one or more source [Property]s by a closure, e.g. an area computed from a
+/// width and a height. It's computed once when created, and then again lazily, the next time it's
+/// read after a binding to any of its sources committed a change.
+///
+/// Reading works like [Property::bind_ref]. Sources are read through
+/// [try_bind_ref](Property::try_bind_ref) while recomputing. If a source is bound mutably at that
+/// point, the cached value is returned instead, and the value is recomputed on a later read. If
+/// the closure panics, the panic is passed on to the reader, and the value is recomputed on the
+/// next read.
+///
+/// ```rust
+/// let w = binder::Property::new(2.0f32);
+/// let h = binder::Property::new(3.0f32);
+/// let area = binder::computed((&w, &h), |w, h| w * h);
+/// assert_eq!(*area.bind_ref(), 6.0);
+/// *w.bind() = 4.0;
+/// assert_eq!(*area.bind_ref(), 12.0);
+/// ```
+pub struct Computed<'a, T> {
+    value: Property<T>,
+    dirty: Arc<AtomicBool>,
+    compute: Box<dyn Fn() -> Result<T, BindError> + Send + Sync + 'a>,
+    _sources: Vec<Subscription>,
+}
+
+impl<'a, T> Computed<'a, T> {
+    /// Creates a computed value from a set of sources and a closure that takes a reference to
+    /// each source's value, in the same order.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if any of the sources is bound mutably.
+    #[track_caller]
+    pub fn new<S, F>(sources: S, f: F) -> Self
+        where S: Sources<'a, F, T> + Send + Sync + 'a,
+              F: Send + Sync + 'a
+    {
+        let dirty = Arc::new(AtomicBool::new(false));
+        let subscriptions = sources.watch(&dirty);
+        let initial = match sources.compute(&f) {
+            Ok(value) => value,
+            Err(e) => bind_panic::<T>("Computed", "already bound mutably", e)
+        };
+        Computed {
+            value: Property::new(initial),
+            dirty,
+            compute: Box::new(move || sources.compute(&f)),
+            _sources: subscriptions,
+        }
+    }
+
+    /// Returns `true` if a source changed since the value was last computed.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Recomputes the value if it's dirty and nobody is reading it right now.
+    fn refresh(&self, location: &'static Location<'static>) -> Result<(), BindError> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+        match self.value.mut_lock.try_write(location) {
+            Ok(ticket) => {
+                let mut binding = self.value.binding(ticket);
+                // cleared before computing, so changes made in the meantime aren't lost
+                self.dirty.store(false, Ordering::Release);
+                // caught so the binding isn't dropped during the panic, which would poison the
+                // cached value for good
+                match std::panic::catch_unwind(AssertUnwindSafe(|| (self.compute)())) {
+                    Ok(Ok(value)) => {
+                        *binding = value;
+                        Ok(())
+                    }
+                    Ok(Err(e)) => {
+                        self.dirty.store(true, Ordering::Release);
+                        match e {
+                            // a source is still bound, e.g. by a binding that's committing the
+                            // change that made this dirty, so try again on a later read
+                            BindError::AlreadyBound(_) | BindError::WouldDeadlock(_) => Ok(()),
+                            e => Err(e)
+                        }
+                    }
+                    Err(panic) => {
+                        self.dirty.store(true, Ordering::Release);
+                        drop(binding);
+                        std::panic::resume_unwind(panic)
+                    }
+                }
+            }
+            // still being read (or recomputed) elsewhere, so keep serving the cached value
+            Err(BindError::AlreadyBound(_)) => Ok(()),
+            Err(e) => Err(e)
+        }
+    }
+
+    /// Reads the value, recomputing it first if any source changed. If the cached value is being
+    /// read elsewhere at the time, or one of the sources is bound mutably, it can't be
+    /// recomputed, so the stale value is returned and the value is recomputed on a later read.
+    /// If another thread is recomputing it, the calling thread is parked until that's done.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the value had to be recomputed and one of the sources is poisoned, or
+    /// if the closure panics.
+    #[track_caller]
+    pub fn bind_ref(&self) -> PropertyReadBinding<'_, T> {
+        match self.try_bind_ref() {
+            Ok(binding) => binding,
+            Err(e) => bind_panic::<T>("Computed", "already bound mutably", e)
+        }
+    }
+
+    /// Safer alternative to [bind_ref](Computed::bind_ref).
+    #[track_caller]
+    pub fn try_bind_ref(&self) -> Result<PropertyReadBinding<'_, T>, BindError> {
+        let location = Location::caller();
+        self.refresh(location)?;
+        // only ever bound mutably by `refresh` on another thread, so wait for it to finish
+        let ticket = self.value.mut_lock.read_blocking(location)?;
+        Ok(self.value.read_binding(ticket))
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Computed<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Computed")
+            .field("value", &self.value)
+            .field("dirty", &self.is_dirty())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Shorthand for [Computed::new].
+#[track_caller]
+pub fn computed<'a, S, F, T>(sources: S, f: F) -> Computed<'a, T>
+    where S: Sources<'a, F, T> + Send + Sync + 'a,
+          F: Send + Sync + 'a
+{
+    Computed::new(sources, f)
+}
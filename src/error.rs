@@ -0,0 +1,352 @@
+//! Error types.
+
+
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+use std::hash::{Hash, Hasher};
+use std::thread::{Thread, ThreadId};
+
+
+/// Where and by whom a property was bound. Only recorded in debug builds
+/// (`debug_assertions`), see [BindError::holder].
+#[derive(Debug, Clone)]
+pub struct Holder {
+    pub(crate) location: &'static Location<'static>,
+    /// Cheap to clone, and the name is only looked up when it's needed.
+    pub(crate) thread: Thread,
+    pub(crate) mutable: bool,
+}
+
+impl Holder {
+    /// The source location of the call that bound the property.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// The thread that bound the property.
+    pub fn thread_id(&self) -> ThreadId {
+        self.thread.id()
+    }
+
+    /// The name of the thread that bound the property, if it has one.
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread.name()
+    }
+
+    /// Whether the property is bound mutably, as opposed to bound for reading.
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+}
+
+impl PartialEq for Holder {
+    fn eq(&self, other: &Self) -> bool {
+        self.location == other.location && self.thread.id() == other.thread.id() && self.mutable == other.mutable
+    }
+}
+
+impl Eq for Holder {}
+
+impl Hash for Holder {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.location.hash(state);
+        self.thread.id().hash(state);
+        self.mutable.hash(state);
+    }
+}
+
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.mutable { "mutably" } else { "for reading" };
+        match self.thread.name() {
+            Some(name) => write!(f, "bound {} at {} on thread '{}'", kind, self.location, name),
+            None => write!(f, "bound {} at {} on thread {:?}", kind, self.location, self.thread.id())
+        }
+    }
+}
+
+/// The reason a [Property](crate::Property) couldn't be bound.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BindError {
+    /// The property is already bound elsewhere, and the binding is incompatible with the one that
+    /// was requested. Carries the current [Holder] in debug builds.
+    AlreadyBound(Option<Holder>),
+    /// The property is already bound mutably by the calling thread, so waiting for it to be
+    /// released would never finish. Carries the current [Holder] in debug builds.
+    ///
+    /// The owner of a binding is the thread that created it. A
+    /// [PropertyBinding](crate::PropertyBinding) that was sent to another thread is still
+    /// reported as bound by the thread that created it, so this is only a hint, and the blocking
+    /// methods don't rely on it: [bind_blocking](crate::Property::bind_blocking) and friends
+    /// wait anyway.
+    WouldDeadlock(Option<Holder>),
+    /// A binding to the property was dropped while its thread was panicking, so the value may be
+    /// in an inconsistent state.
+    Poisoned,
+    /// The property was still bound elsewhere when the timeout ran out.
+    TimedOut,
+}
+
+impl BindError {
+    /// The binding that was in the way, if it was recorded. Holders are only recorded in debug
+    /// builds, so this is always `None` in release builds.
+    pub fn holder(&self) -> Option<&Holder> {
+        match self {
+            BindError::AlreadyBound(holder) | BindError::WouldDeadlock(holder) => holder.as_ref(),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for BindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindError::AlreadyBound(_) => write!(f, "property is already bound")?,
+            BindError::WouldDeadlock(_) => write!(f, "property is already bound by this thread (would deadlock)")?,
+            BindError::Poisoned => write!(f, "property is poisoned")?,
+            BindError::TimedOut => write!(f, "timed out waiting for property to be unbound")?,
+        }
+        if let Some(holder) = self.holder() {
+            write!(f, " ({})", holder)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for BindError {}
+
+/// The reason two properties couldn't be [link](crate::link)ed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LinkError {
+    /// Both sides are the same property.
+    SameProperty,
+    /// The properties are already connected through other links, so linking them would create a
+    /// cycle.
+    Cycle,
+    /// One of the properties couldn't be bound to sync their values.
+    Bind(BindError),
+    /// The second property's validators rejected the first one's value.
+    Validation(ValidationError),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::SameProperty => write!(f, "can't link a property to itself"),
+            LinkError::Cycle => write!(f, "properties are already linked (would create a cycle)"),
+            LinkError::Bind(e) => write!(f, "couldn't sync linked properties: {}", e),
+            LinkError::Validation(e) => write!(f, "couldn't sync linked properties: {}", e),
+        }
+    }
+}
+
+impl Error for LinkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LinkError::Bind(e) => Some(e),
+            LinkError::Validation(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<BindError> for LinkError {
+    fn from(e: BindError) -> Self {
+        LinkError::Bind(e)
+    }
+}
+
+/// The reason an [UndoStack](crate::UndoStack) entry couldn't be undone or redone.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UndoError {
+    /// One of the entry's properties couldn't be bound.
+    Bind(BindError),
+    /// One of the entry's properties rejected the value it was restored to.
+    Validation(ValidationError),
+}
+
+impl fmt::Display for UndoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndoError::Bind(e) => write!(f, "couldn't bind property: {}", e),
+            UndoError::Validation(e) => write!(f, "couldn't restore value: {}", e),
+        }
+    }
+}
+
+impl Error for UndoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UndoError::Bind(e) => Some(e),
+            UndoError::Validation(e) => Some(e),
+        }
+    }
+}
+
+impl From<BindError> for UndoError {
+    fn from(e: BindError) -> Self {
+        UndoError::Bind(e)
+    }
+}
+
+impl From<ValidationError> for UndoError {
+    fn from(e: ValidationError) -> Self {
+        UndoError::Validation(e)
+    }
+}
+
+/// A changed value was rejected by one of a [Property](crate::Property)'s validators, see
+/// [with_validator](crate::Property::with_validator).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidationError {
+    message: String,
+}
+
+impl ValidationError {
+    pub(crate) fn new(message: String) -> Self {
+        ValidationError { message }
+    }
+
+    /// The message returned by the validator.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid value: {}", self.message)
+    }
+}
+
+impl Error for ValidationError {}
+
+/// The reason a [PropertyTree](crate::PropertyTree) operation failed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TreeError {
+    /// The path is empty, has an empty segment, or contains characters other than ASCII letters,
+    /// digits, `_` and `-` between the dots.
+    InvalidPath(String),
+    /// Another property is already registered under the path.
+    AlreadyRegistered(String),
+    /// No property is registered under the path.
+    NotFound(String),
+    /// The property registered under the path has a different value type than the one that was
+    /// asked for.
+    TypeMismatch {
+        /// The path of the property.
+        path: String,
+        /// The type that was asked for.
+        expected: &'static str,
+        /// The type of the registered property.
+        found: &'static str,
+    },
+    /// The property was found, but couldn't be bound.
+    Bind(BindError),
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::InvalidPath(path) => write!(f, "invalid property path '{}'", path),
+            TreeError::AlreadyRegistered(path) => write!(f, "a property is already registered at '{}'", path),
+            TreeError::NotFound(path) => write!(f, "no property registered at '{}'", path),
+            TreeError::TypeMismatch { path, expected, found } =>
+                write!(f, "property at '{}' has type {}, not {}", path, found, expected),
+            TreeError::Bind(e) => write!(f, "couldn't bind property: {}", e),
+        }
+    }
+}
+
+impl Error for TreeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TreeError::Bind(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<BindError> for TreeError {
+    fn from(e: BindError) -> Self {
+        TreeError::Bind(e)
+    }
+}
+
+/// A line of text that couldn't be applied by [PropertyTree::load](crate::PropertyTree::load).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadError {
+    pub(crate) line: usize,
+    pub(crate) path: Option<String>,
+    pub(crate) kind: LoadErrorKind,
+}
+
+impl LoadError {
+    /// The line number, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The path on that line, if it got far enough to read one.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// What went wrong.
+    pub fn kind(&self) -> &LoadErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "line {} ('{}'): {}", self.line, path, self.kind),
+            None => write!(f, "line {}: {}", self.line, self.kind)
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            LoadErrorKind::Validation(e) => Some(e),
+            LoadErrorKind::Bind(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+/// The reason a [LoadError] happened.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LoadErrorKind {
+    /// The line isn't a `path = value` pair, or the value is a malformed string.
+    Syntax(String),
+    /// No property was registered under the path with
+    /// [register_persistent](crate::PropertyTree::register_persistent).
+    UnknownKey,
+    /// The value couldn't be parsed into the property's type. Carries the parse error's message.
+    Parse(String),
+    /// The value was rejected by one of the property's validators.
+    Validation(ValidationError),
+    /// The property couldn't be bound to set the value.
+    Bind(BindError),
+}
+
+impl fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadErrorKind::Syntax(message) => write!(f, "syntax error: {}", message),
+            LoadErrorKind::UnknownKey => write!(f, "no persistent property registered at this path"),
+            LoadErrorKind::Parse(message) => write!(f, "couldn't parse value: {}", message),
+            LoadErrorKind::Validation(e) => write!(f, "{}", e),
+            LoadErrorKind::Bind(e) => write!(f, "couldn't bind property: {}", e),
+        }
+    }
+}
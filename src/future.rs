@@ -0,0 +1,82 @@
+//! Binding properties from async code.
+
+
+use std::future::Future;
+use std::panic::Location;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use crate::{bind_panic, BindError, Property, PropertyBinding};
+use crate::lock::Waiter;
+
+
+/// Future returned by [Property::bind_async]. Resolves to a [PropertyBinding] once the property
+/// is free. Only uses [std::task], so it works with any executor.
+///
+/// Waiting tasks are queued along with threads waiting in
+/// [bind_blocking](Property::bind_blocking), and served in the same FIFO order. Dropping the
+/// future gives up its place in the queue.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct BindFuture<'a, T> {
+    property: &'a Property<T>,
+    location: &'static Location<'static>,
+    waiter: Option<Arc<Waiter>>,
+}
+
+impl<'a, T> BindFuture<'a, T> {
+    pub(crate) fn new(property: &'a Property<T>, location: &'static Location<'static>) -> Self {
+        BindFuture { property, location, waiter: None }
+    }
+
+    fn ready(&self, result: Result<crate::lock::Ticket, BindError>) -> Poll<PropertyBinding<'a, T>> {
+        match result {
+            Ok(ticket) => Poll::Ready(self.property.binding(ticket)),
+            Err(e) => bind_panic::<T>("PropertyBinding", "already bound", e)
+        }
+    }
+}
+
+impl<'a, T> Future for BindFuture<'a, T> {
+    type Output = PropertyBinding<'a, T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let lock = &self.property.mut_lock;
+        match &self.waiter {
+            Some(waiter) => {
+                waiter.set_waker(cx.waker());
+                if !waiter.is_granted() {
+                    return Poll::Pending;
+                }
+                self.waiter = None;
+                let result = self.property.mut_lock.acquired_write(self.location);
+                self.ready(result)
+            }
+            None => match lock.try_write(self.location) {
+                // the holder may be another task on this thread, which can still make progress
+                Err(BindError::AlreadyBound(_)) | Err(BindError::WouldDeadlock(_)) => {
+                    let waiter = Waiter::task(cx.waker());
+                    if lock.enqueue(&waiter) {
+                        let result = lock.acquired_write(self.location);
+                        return self.ready(result);
+                    }
+                    self.waiter = Some(waiter);
+                    Poll::Pending
+                }
+                result => self.ready(result)
+            }
+        }
+    }
+}
+
+impl<'a, T> Drop for BindFuture<'a, T> {
+    fn drop(&mut self) {
+        if let Some(waiter) = self.waiter.take() {
+            if self.property.mut_lock.dequeue(&waiter) {
+                // handed the lock right before being dropped, so pass it on
+                drop(self.property.mut_lock.release_write());
+            }
+        }
+    }
+}
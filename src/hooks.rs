@@ -0,0 +1,218 @@
+//! Per-property hooks that run when a [PropertyBinding](crate::PropertyBinding) commits.
+//!
+//! Hooks are allocated lazily, so properties that never get an observer don't pay for them.
+
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, Weak};
+
+use crate::ValidationError;
+
+
+type ChangeObserver<T> = Arc<dyn Fn(&T, &T) + Send + Sync>;
+type CommitObserver<T> = Arc<dyn Fn(&T) + Send + Sync>;
+type RejectValidator<T> = Arc<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+type CoerceValidator<T> = Arc<dyn Fn(&mut T) + Send + Sync>;
+type UnlockObserver = Arc<dyn Fn() + Send + Sync>;
+
+enum Observer<T> {
+    /// Gets the old and new value, so it needs a snapshot of the old one.
+    Change(ChangeObserver<T>),
+    /// Only gets the new value.
+    Commit(CommitObserver<T>),
+    /// Called once the binding that committed a change has unbound the property, so it can bind
+    /// the property itself. Doesn't get the value.
+    Unlocked(UnlockObserver),
+}
+
+impl<T> Clone for Observer<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Observer::Change(o) => Observer::Change(o.clone()),
+            Observer::Commit(o) => Observer::Commit(o.clone()),
+            Observer::Unlocked(o) => Observer::Unlocked(o.clone()),
+        }
+    }
+}
+
+pub(crate) enum Validator<T> {
+    /// Rolls the value back if it returns an error.
+    Reject(RejectValidator<T>),
+    /// Fixes up the value in place.
+    Coerce(CoerceValidator<T>),
+}
+
+impl<T> Clone for Validator<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Validator::Reject(v) => Validator::Reject(v.clone()),
+            Validator::Coerce(v) => Validator::Coerce(v.clone()),
+        }
+    }
+}
+
+pub(crate) struct HookList<T> {
+    next_id: u64,
+    /// Used to take a copy of the value before it's first mutated. Only set once a hook that
+    /// needs the old value has been registered, which is also the only place `T: Clone` is known.
+    snapshot: Option<fn(&T) -> T>,
+    observers: Vec<(u64, Observer<T>)>,
+    validators: Vec<Validator<T>>,
+}
+
+/// Storage for a property's hooks. Empty until the first hook is registered.
+pub(crate) struct Hooks<T> {
+    list: OnceLock<Arc<Mutex<HookList<T>>>>,
+}
+
+fn lock<T>(list: &Mutex<HookList<T>>) -> MutexGuard<'_, HookList<T>> {
+    // the lock is never held while user code runs, so poisoning can't leave the list half-updated
+    list.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<T> Hooks<T> {
+    pub(crate) const fn new() -> Self {
+        Hooks { list: OnceLock::new() }
+    }
+
+    fn list(&self) -> &Arc<Mutex<HookList<T>>> {
+        self.list.get_or_init(|| Arc::new(Mutex::new(HookList {
+            next_id: 0,
+            snapshot: None,
+            observers: Vec::new(),
+            validators: Vec::new(),
+        })))
+    }
+
+    /// Copies the value if any registered hook needs to know what it was before a change.
+    pub(crate) fn snapshot(&self, value: &T) -> Option<T> {
+        let snapshot = lock(self.list.get()?).snapshot;
+        snapshot.map(|f| f(value))
+    }
+
+    /// Runs every validator on a changed value, in the order they were added. Stops at the first
+    /// one that rejects the value.
+    pub(crate) fn validate(&self, value: &mut T) -> Result<(), ValidationError> {
+        let validators: Vec<Validator<T>> = match self.list.get() {
+            Some(list) => lock(list).validators.clone(),
+            None => return Ok(())
+        };
+        for validator in validators {
+            match validator {
+                Validator::Reject(v) => v(value).map_err(ValidationError::new)?,
+                Validator::Coerce(v) => v(value),
+            }
+        }
+        Ok(())
+    }
+
+    /// Calls every observer after a change. Observers that need the old value are skipped if
+    /// there's no snapshot of it, which only happens if they were registered mid-binding.
+    pub(crate) fn notify(&self, old: Option<&T>, new: &T) {
+        let observers: Vec<Observer<T>> = match self.list.get() {
+            Some(list) => lock(list).observers.iter().map(|(_, o)| o.clone()).collect(),
+            None => return
+        };
+        for observer in observers {
+            match (observer, old) {
+                (Observer::Change(o), Some(old)) => o(old, new),
+                (Observer::Change(_), None) => {}
+                (Observer::Commit(o), _) => o(new),
+                (Observer::Unlocked(_), _) => {}
+            }
+        }
+    }
+
+    /// Calls the observers that wait for the property to be unbound after a change.
+    pub(crate) fn notify_unlocked(&self) {
+        let observers: Vec<UnlockObserver> = match self.list.get() {
+            Some(list) => lock(list).observers.iter()
+                .filter_map(|(_, o)| match o { Observer::Unlocked(o) => Some(o.clone()), _ => None })
+                .collect(),
+            None => return
+        };
+        for observer in observers {
+            observer();
+        }
+    }
+}
+
+impl<T: 'static> Hooks<T> {
+    fn add(&self, observer: Observer<T>, snapshot: Option<fn(&T) -> T>) -> Subscription {
+        let list = self.list();
+        let id = {
+            let mut list = lock(list);
+            if snapshot.is_some() {
+                list.snapshot = snapshot;
+            }
+            let id = list.next_id;
+            list.next_id += 1;
+            list.observers.push((id, observer));
+            id
+        };
+        let weak: Weak<Mutex<HookList<T>>> = Arc::downgrade(list);
+        Subscription {
+            unsubscribe: Some(Box::new(move || {
+                if let Some(list) = weak.upgrade() {
+                    lock(&list).observers.retain(|(i, _)| *i != id);
+                }
+            }))
+        }
+    }
+
+    /// Adds a validator. Validators that reject values need a snapshot to roll back to.
+    pub(crate) fn add_validator(&self, validator: Validator<T>, snapshot: Option<fn(&T) -> T>) {
+        let mut list = lock(self.list());
+        if snapshot.is_some() {
+            list.snapshot = snapshot;
+        }
+        list.validators.push(validator);
+    }
+
+    /// Registers an observer that only needs the new value after each commit.
+    pub(crate) fn watch(&self, observer: CommitObserver<T>) -> Subscription {
+        self.add(Observer::Commit(observer), None)
+    }
+
+    /// Registers an observer that's called after each commit, once the property is unbound.
+    pub(crate) fn watch_unlocked(&self, observer: UnlockObserver) -> Subscription {
+        self.add(Observer::Unlocked(observer), None)
+    }
+}
+
+impl<T: Clone + 'static> Hooks<T> {
+    pub(crate) fn subscribe(&self, observer: ChangeObserver<T>) -> Subscription {
+        self.add(Observer::Change(observer), Some(T::clone))
+    }
+}
+
+impl<T> fmt::Debug for Hooks<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (observers, validators) = self.list.get()
+            .map_or((0, 0), |list| { let list = lock(list); (list.observers.len(), list.validators.len()) });
+        f.debug_struct("Hooks").field("observers", &observers).field("validators", &validators).finish()
+    }
+}
+
+
+/// Handle to an observer registered with [Property::subscribe](crate::Property::subscribe).
+/// The observer is unsubscribed when the `Subscription` is [Drop](std::ops::Drop)ped. Dropping it
+/// after the property itself is gone is fine and does nothing.
+#[must_use = "the observer is unsubscribed as soon as the Subscription is dropped"]
+pub struct Subscription {
+    unsubscribe: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription").finish_non_exhaustive()
+    }
+}
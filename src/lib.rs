@@ -11,6 +11,17 @@
 //! XOR muliple immutable access to the binding itself. The binding unbinds itself when
 //! [Drop](std::ops::Drop)ped, so it is automatically freed when it exits scope.
 //!
+//! Dropping a binding that was mutably dereferenced commits the change. Observers registered with
+//! [subscribe()](Property::subscribe) are notified with the old and new value at that point, so
+//! there's no need to poll properties for changes. Changes can also be checked, and rejected or
+//! fixed up, by validators added with [with_validator()](Property::with_validator) and friends.
+//! Values derived from other properties can be kept up to date automatically with [Computed], and
+//! two properties holding the same value can be kept in sync with [link()]. Changes to any number
+//! of properties can be recorded in an [UndoStack] and undone later, or made all at once with a
+//! [Transaction] that can still be rolled back. Properties shared through [Arc](std::sync::Arc)s
+//! can be registered by name in a [PropertyTree], which can also save their values as text and
+//! load them again.
+//!
 //! ### Example
 //!
 //! ```rust
@@ -23,26 +34,103 @@
 //! }
 //! ```
 //!
+//! Code that only reads a property can use [bind_ref()](Property::bind_ref) instead. It returns a
+//! [PropertyReadBinding] which only [Deref](std::ops::Deref)s, and any number of those can exist
+//! at the same time.
+//!
+//! Simple reads and writes don't need a binding of their own: [get()](Property::get),
+//! [set()](Property::set), [update()](Property::update) and friends bind the property just for
+//! the duration of the call, and each has a `try_` version that returns the [BindError] instead of
+//! panicking.
+//!
+//! Single-threaded code can use [LocalProperty] instead, which has the same API but doesn't need
+//! atomics, and code that binds properties can accept either kind through the [Bindable] trait.
+//! Small [Copy] values that are only ever read and written whole can be kept in an
+//! [AtomicProperty], which doesn't need to be bound at all.
+//!
+//! # Features
+//!
+//! - `derive`: `#[derive(Properties)]` for structs made of properties, see [Properties]. With
+//!   `#[properties(reflect)]`, those structs also implement [Reflect], so their properties can be
+//!   found by name at runtime.
+//! - `serde`: `Serialize` and `Deserialize` for `Property<T>`. Serializing binds the value for
+//!   reading, and fails if the property is bound mutably. Deserializing creates a new property.
+//!
 //! # Safety
 //!
-//! `Property` owns its value and maintains its own invariants over that value. Properties cannot
-//! be bound more than once at the same time. The thread-safe [AtomicBool](std::sync::atomic::AtomicBool)
-//! is used to synchronize access to the binding, so it should be fully thread-safe as well.
+//! `Property` owns its value and maintains its own invariants over that value. Properties can
+//! be bound mutably by only one binding at a time, which excludes any read-only bindings. A
+//! thread-safe [AtomicUsize](std::sync::atomic::AtomicUsize) reader/writer lock, stored inside
+//! the property itself, is used to synchronize access to the bindings, so it should be fully
+//! thread-safe as well. Bindings borrow that lock, so binding a property doesn't allocate.
+//! Threads waiting for a property, and the holders recorded in debug builds, are kept in global
+//! tables rather than in the property, so a property is only a few words bigger than its value.
 //!
-//! Properties CANNOT be cloned to get more references to the same value. You can use
-//! [Rc](std::rc::Rc)`<Property>` or [Arc](std::sync::Arc)`<Property>` for that.
+//! Properties CANNOT be cloned to get more references to the same value. Cloning a `Property`
+//! creates a new, independent one with a copy of the value. To share a property, use
+//! [Rc](std::rc::Rc)`<Property>` or [Arc](std::sync::Arc)`<Property>` instead. An
+//! `Arc<Property>` can also be bound with [bind_arc()](Property::bind_arc), which returns an owned
+//! [ArcPropertyBinding] that keeps the property alive and isn't tied to a borrow.
 //!
 //! # Panic
 //!
 //! [bind()](Property::bind) will panic if called on a `Property` that's already been bound
-//! elsewhere. Use [try_bind()](Property::try_bind) for a non-panicking version.
+//! elsewhere. Use [try_bind()](Property::try_bind) for a non-panicking version. The same goes for
+//! [bind_ref()](Property::bind_ref) on a mutably bound `Property` and
+//! [try_bind_ref()](Property::try_bind_ref). To wait for a property to be released instead, use
+//! [bind_blocking()](Property::bind_blocking) or [bind_timeout()](Property::bind_timeout), or
+//! `.await` [bind_async()](Property::bind_async) from async code. Several properties that need
+//! to be edited together can be bound all at once with [bind_all()] or [try_bind_all()].
+//!
+//! Like [Mutex](std::sync::Mutex), a `Property` is poisoned if a mutable binding to it is dropped
+//! during a panic, or if one of its validators or observers panics, and binding a poisoned
+//! property fails until [clear_poison()](Property::clear_poison) is called.
 
 
 use std::cell::UnsafeCell;
-use std::ops::{Deref, DerefMut};
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, RangeInclusive};
+use std::panic::Location;
 use std::ptr::NonNull;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+mod atomic;
+mod bindable;
+mod computed;
+mod error;
+mod future;
+mod hooks;
+mod link;
+mod local;
+mod lock;
+mod multi;
+mod properties;
+mod reflect;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod transaction;
+mod tree;
+mod undo;
+
+pub use atomic::{AtomicProperty, AtomicPropertyBinding};
+pub use bindable::Bindable;
+pub use computed::{computed, Computed, Sources};
+pub use error::{BindError, Holder, LinkError, LoadError, LoadErrorKind, TreeError, UndoError, ValidationError};
+pub use future::BindFuture;
+use hooks::{Hooks, Validator};
+use lock::{BindLock, Released, Ticket};
+pub use hooks::Subscription;
+pub use link::{link, Link};
+pub use local::{LocalProperty, LocalPropertyBinding, LocalPropertyReadBinding, LocalSubscription};
+pub use multi::{bind_all, try_bind_all, BindAll};
+pub use properties::{FieldInfo, FieldVisitor, Properties};
+#[cfg(feature = "derive")]
+pub use binder_derive::Properties;
+pub use reflect::{AnyProperty, AnyPropertyBinding, AnyPropertyReadBinding, PropertyVisitor, Reflect};
+pub use transaction::Transaction;
+pub use tree::PropertyTree;
+pub use undo::{UndoGroup, UndoStack};
 
 
 #[derive(Debug)]
@@ -51,16 +139,95 @@ use std::sync::atomic::{AtomicBool, Ordering};
 /// `PropertyBinding` automatically unbinds itself when [Drop](std::ops::Drop)ped, and allowing
 /// the binding to leave scope and be [Drop](std::ops::Drop)ped is the preferred usage.
 ///
+/// The binding borrows the [Property] it was created from for its whole lifetime `'a`, so the
+/// borrow checker won't let the property be moved or dropped while it's still bound.
+///
+/// Dropping a binding that was mutably dereferenced commits the change. The new value is checked
+/// by the property's validators (see [with_validator](Property::with_validator)) first, and if
+/// it's accepted, any observers registered with [subscribe](Property::subscribe) are notified.
+/// [commit](PropertyBinding::commit) does the same, but reports whether the value was accepted.
+/// If the binding is dropped while its thread is panicking, the property is poisoned instead,
+/// see [clear_poison](Property::clear_poison). So is a property whose validators or observers
+/// panic while the binding commits; it's unbound either way.
+///
 /// # Safety
 ///
 /// Cannot be cloned, as it is assumed to have an exclusive lock on the property.
 /// Thread-safe but not shareable. [Send](core::marker::Send) but not [Sync](core::marker::Sync).
-pub struct PropertyBinding<T> {
+pub struct PropertyBinding<'a, T> {
     value: NonNull<T>,
-    lock: Arc<AtomicBool>,
+    lock: &'a BindLock,
+    ticket: Ticket,
+    hooks: &'a Hooks<T>,
+    /// The value from before the first mutable dereference, if any hooks need it.
+    old: Option<T>,
+    dirty: bool,
+    /// Set once a change was committed, so hooks that wait for the unlock are called on drop.
+    committed: bool,
+    /// Set once the value has been put back the way it was, so dropping the binding during a
+    /// panic doesn't poison the property.
+    restored: bool,
+}
+
+impl<'a, T> PropertyBinding<'a, T> {
+    /// Runs the validators on the value, if it was changed.
+    fn validate(&mut self) -> Result<(), ValidationError> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.hooks.validate(unsafe { self.value.as_mut() })
+    }
+
+    /// Notifies observers of a validated change.
+    fn notify(&mut self) {
+        if self.dirty {
+            self.dirty = false;
+            self.committed = true;
+            let old = self.old.take();
+            self.hooks.notify(old.as_ref(), unsafe { self.value.as_ref() });
+        }
+    }
+
+    /// Forgets about the change, so it won't be validated or observed.
+    fn discard(&mut self) {
+        self.dirty = false;
+        self.old = None;
+    }
+
+    /// Puts the value back the way it was before the property was bound, and forgets about the
+    /// change.
+    fn restore(&mut self, original: T) {
+        *unsafe { self.value.as_mut() } = original;
+        self.discard();
+        self.restored = true;
+    }
+
+    /// Validates and commits the change, notifying observers if it was accepted.
+    fn finish(&mut self) -> Result<(), ValidationError> {
+        match self.validate() {
+            Ok(()) => {
+                self.notify();
+                Ok(())
+            }
+            Err(e) => {
+                if let Some(old) = self.old.take() {
+                    *unsafe { self.value.as_mut() } = old;
+                }
+                self.discard();
+                Err(e)
+            }
+        }
+    }
+
+    /// Unbinds the property, like dropping the binding would, and returns whether the change was
+    /// accepted by the property's validators. A rejected change has already been rolled back when
+    /// this returns.
+    pub fn commit(mut self) -> Result<(), ValidationError> {
+        self.finish()
+    }
 }
 
-impl<T> Deref for PropertyBinding<T> {
+impl<'a, T> Deref for PropertyBinding<'a, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -68,35 +235,192 @@ impl<T> Deref for PropertyBinding<T> {
     }
 }
 
-impl<T> DerefMut for PropertyBinding<T> {
+impl<'a, T> DerefMut for PropertyBinding<'a, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
+        if !self.dirty {
+            self.dirty = true;
+            self.old = self.hooks.snapshot(unsafe { self.value.as_ref() });
+        }
         unsafe { self.value.as_mut() }
     }
 }
 
-impl<T> Drop for PropertyBinding<T> {
+impl<'a, T> Drop for PropertyBinding<'a, T> {
     fn drop(&mut self) {
-        if self.lock.swap(false, Ordering::SeqCst) == false {
-            panic!("PropertyBinding<{}>: Tried to drop a lock that was already unlocked!", std::any::type_name::<T>())
+        let mut unlock = Unlock::<T> { lock: self.lock, ticket: Some(self.ticket), poison: !self.restored, _type: PhantomData };
+        // if the thread is already panicking, the value may have been left half-modified, so
+        // don't commit it. `unlock` poisons the property in that case, and also if a validator or
+        // observer panics.
+        if !std::thread::panicking() {
+            let _ = self.finish();
+        }
+        let released = unlock.release();
+        if self.committed {
+            self.hooks.notify_unlocked();
+        }
+        // anything that was waiting for the property to be unbound comes after this binding's own
+        // observers, since it's older than the change they see
+        drop(released);
+    }
+}
+
+/// Releases a [PropertyBinding]'s lock when dropped, even if its hooks panic. Poisons the
+/// property if that happens while the thread is panicking, unless `poison` is cleared.
+struct Unlock<'a, T> {
+    lock: &'a BindLock,
+    ticket: Option<Ticket>,
+    poison: bool,
+    _type: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> Unlock<'a, T> {
+    fn release(&mut self) -> Released {
+        let Some(ticket) = self.ticket.take() else { return Released::default() };
+        let panicking = std::thread::panicking();
+        if panicking && self.poison {
+            self.lock.poison();
+        }
+        match self.lock.unlock_write(ticket) {
+            Some(released) => released,
+            // panicking again would abort
+            None if panicking => Released::default(),
+            None => panic!("PropertyBinding<{}>: Tried to drop a lock that was already unlocked!", std::any::type_name::<T>())
         }
     }
 }
 
-unsafe impl<T: Send> Send for PropertyBinding<T> {}
+impl<'a, T> Drop for Unlock<'a, T> {
+    fn drop(&mut self) {
+        drop(self.release());
+    }
+}
+
+unsafe impl<'a, T: Send> Send for PropertyBinding<'a, T> {}
+
+#[derive(Debug)]
+/// A shared, read-only binding to a [Property], returned by [bind_ref()](Property::bind_ref).
+/// Only [Deref](std::ops::Deref)s to `&T`. Any number of read bindings to the same property can
+/// exist at once, but none can exist alongside a mutable [PropertyBinding]. Unbinds itself when
+/// [Drop](std::ops::Drop)ped, just like `PropertyBinding`.
+///
+/// # Safety
+///
+/// Hands out shared references to the value, so it's only [Send](core::marker::Send) and
+/// [Sync](core::marker::Sync) if `T` is [Sync](core::marker::Sync).
+pub struct PropertyReadBinding<'a, T> {
+    value: NonNull<T>,
+    lock: &'a BindLock,
+    ticket: Ticket,
+    _property: PhantomData<&'a Property<T>>,
+}
+
+impl<'a, T> Deref for PropertyReadBinding<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.value.as_ref() }
+    }
+}
+
+impl<'a, T> Drop for PropertyReadBinding<'a, T> {
+    fn drop(&mut self) {
+        if self.lock.unlock_read(self.ticket).is_none() {
+            panic!("PropertyReadBinding<{}>: Tried to drop a lock that was already unlocked!", std::any::type_name::<T>())
+        }
+    }
+}
+
+unsafe impl<'a, T: Sync> Send for PropertyReadBinding<'a, T> {}
+unsafe impl<'a, T: Sync> Sync for PropertyReadBinding<'a, T> {}
+
+/// An owned binding to a [Property] shared through an [Arc](std::sync::Arc), returned by
+/// [bind_arc()](Property::bind_arc). Works just like a [PropertyBinding], except it holds on to
+/// its own reference to the property instead of borrowing it, so it has no lifetime and can be
+/// stored in structs or sent to other threads. The property is kept alive for at least as long
+/// as the binding exists.
+///
+/// # Safety
+///
+/// Same rules as [PropertyBinding]: exclusive, [Send](core::marker::Send) but not
+/// [Sync](core::marker::Sync). The inner `Arc` is never handed out, so the binding can't be used
+/// to get more references to the property.
+pub struct ArcPropertyBinding<T: 'static> {
+    // declared before `property` so the lock, which lives in the Arc, is released before the Arc
+    // is dropped
+    binding: PropertyBinding<'static, T>,
+    property: Arc<Property<T>>,
+}
+
+impl<T: 'static> ArcPropertyBinding<T> {
+    /// See [PropertyBinding::commit].
+    pub fn commit(self) -> Result<(), ValidationError> {
+        self.binding.commit()
+    }
+}
+
+impl<T: 'static> Deref for ArcPropertyBinding<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.binding
+    }
+}
+
+impl<T: 'static> DerefMut for ArcPropertyBinding<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.binding
+    }
+}
+
+impl<T: std::fmt::Debug + 'static> std::fmt::Debug for ArcPropertyBinding<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArcPropertyBinding")
+            .field("binding", &self.binding)
+            .field("property", &Arc::as_ptr(&self.property))
+            .finish()
+    }
+}
+
+unsafe impl<T: Send + 'static> Send for ArcPropertyBinding<T> {}
 
 /// Used to define a bindable property. See crate-level documentation for more details.
 #[derive(Debug)]
 pub struct Property<T> {
     property: UnsafeCell<T>,
-    mut_lock: Arc<AtomicBool>
+    mut_lock: BindLock,
+    hooks: Hooks<T>,
 }
 
 impl<T> Property<T> {
-    /// Creates a new `Property` that owns the given value.
-    pub fn new(value: T) -> Self {
+    /// Creates a new `Property` that owns the given value. Doesn't allocate, and can be used to
+    /// initialize `static`s.
+    pub const fn new(value: T) -> Self {
         Property {
             property: UnsafeCell::new(value),
-            mut_lock: Arc::new(AtomicBool::new(false))
+            mut_lock: BindLock::new(),
+            hooks: Hooks::new(),
+        }
+    }
+
+    fn binding(&self, ticket: Ticket) -> PropertyBinding<'_, T> {
+        PropertyBinding {
+            value: NonNull::new(self.property.get()).unwrap(),
+            lock: &self.mut_lock,
+            ticket,
+            hooks: &self.hooks,
+            old: None,
+            dirty: false,
+            committed: false,
+            restored: false,
+        }
+    }
+
+    fn read_binding(&self, ticket: Ticket) -> PropertyReadBinding<'_, T> {
+        PropertyReadBinding {
+            value: NonNull::new(self.property.get()).unwrap(),
+            lock: &self.mut_lock,
+            ticket,
+            _property: PhantomData
         }
     }
 
@@ -104,42 +428,409 @@ impl<T> Property<T> {
     ///
     /// # Panics
     ///
-    /// This will panic if called while the property is already bound.
-    pub fn bind(&self) -> PropertyBinding<T> {
-        let was_locked = self.mut_lock.swap(true, Ordering::SeqCst);
-        if was_locked {
-            panic!("PropertyBinding<{}>: Tried to bind a property that was already bound!", std::any::type_name::<T>());
-        }
-        PropertyBinding {
-            value: NonNull::new(self.property.get()).unwrap(),
-            lock: self.mut_lock.clone()
+    /// This will panic if called while the property is already bound. In debug builds, the panic
+    /// message says where and on which thread the existing binding was created. It will also
+    /// panic if the property is poisoned.
+    #[track_caller]
+    pub fn bind(&self) -> PropertyBinding<'_, T> {
+        match self.mut_lock.try_write(Location::caller()) {
+            Ok(ticket) => self.binding(ticket),
+            Err(e) => bind_panic::<T>("PropertyBinding", "already bound", e)
         }
     }
 
     /// Safer alternative to [bind](Property::bind).
     /// Returns [Ok](core::result::Result::Ok)([PropertyBinding\<T\>](PropertyBinding))
-    /// upon successful binding and [Err](core::result::Result::Err)(())
-    /// if the `Property` was already bound.
-    pub fn try_bind(&self) -> Result<PropertyBinding<T>, ()> {
-        let was_locked = self.mut_lock.swap(true, Ordering::SeqCst);
-        if was_locked {
-            Err(())
-        }
-        else {
-            Ok(PropertyBinding {
-                value: NonNull::new(self.property.get()).unwrap(),
-                lock: self.mut_lock.clone()
+    /// upon successful binding and [Err](core::result::Result::Err)([BindError])
+    /// if the `Property` was already bound. The error is [BindError::WouldDeadlock] instead of
+    /// [BindError::AlreadyBound] if the property seems to be bound mutably by the calling thread
+    /// (see [BindError::WouldDeadlock] for the limits of that), and
+    /// [BindError::Poisoned] if the property is poisoned.
+    #[track_caller]
+    pub fn try_bind(&self) -> Result<PropertyBinding<'_, T>, BindError> {
+        let ticket = self.mut_lock.try_write(Location::caller())?;
+        Ok(self.binding(ticket))
+    }
+
+    /// Blocking alternative to [bind](Property::bind). If the property is already bound, the
+    /// calling thread is parked until the binding is dropped. Threads waiting for the same
+    /// property are served in the order they started waiting. Read-only bindings can still be
+    /// created while threads are waiting, so a steady stream of readers can keep a waiting
+    /// thread from ever getting the property.
+    ///
+    /// Returns [BindError::Poisoned] if the property is (or becomes) poisoned. Like
+    /// [Mutex::lock](std::sync::Mutex::lock), waiting for a binding that's held by the calling
+    /// thread blocks forever. Use [bind_timeout](Property::bind_timeout) if that can happen.
+    #[track_caller]
+    pub fn bind_blocking(&self) -> Result<PropertyBinding<'_, T>, BindError> {
+        let ticket = self.mut_lock.write_blocking(Location::caller(), None)?;
+        Ok(self.binding(ticket))
+    }
+
+    /// Like [bind_blocking](Property::bind_blocking), but gives up and returns
+    /// [BindError::TimedOut] if the property is still bound after `timeout`.
+    #[track_caller]
+    pub fn bind_timeout(&self, timeout: Duration) -> Result<PropertyBinding<'_, T>, BindError> {
+        let ticket = self.mut_lock.write_blocking(Location::caller(), Instant::now().checked_add(timeout))?;
+        Ok(self.binding(ticket))
+    }
+
+    /// Async alternative to [bind_blocking](Property::bind_blocking). The returned [BindFuture]
+    /// registers the task's [Waker](std::task::Waker) and is woken when the property is handed to
+    /// it, instead of blocking the thread.
+    ///
+    /// # Panics
+    ///
+    /// The future will panic when polled if the property is poisoned.
+    #[track_caller]
+    pub fn bind_async(&self) -> BindFuture<'_, T> {
+        BindFuture::new(self, Location::caller())
+    }
+
+    /// Binds the property for reading only. Any number of read bindings can exist at the same
+    /// time, even on different threads, but they exclude mutable bindings from
+    /// [bind](Property::bind).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is bound mutably, or if it's poisoned.
+    #[track_caller]
+    pub fn bind_ref(&self) -> PropertyReadBinding<'_, T> {
+        match self.mut_lock.try_read(Location::caller()) {
+            Ok(ticket) => self.read_binding(ticket),
+            Err(e) => bind_panic::<T>("PropertyReadBinding", "already bound mutably", e)
+        }
+    }
+
+    /// Safer alternative to [bind_ref](Property::bind_ref).
+    /// Returns [Err](core::result::Result::Err)([BindError]) if the `Property` was already
+    /// bound mutably or is poisoned.
+    #[track_caller]
+    pub fn try_bind_ref(&self) -> Result<PropertyReadBinding<'_, T>, BindError> {
+        let ticket = self.mut_lock.try_read(Location::caller())?;
+        Ok(self.read_binding(ticket))
+    }
+
+    /// Returns `true` if a mutable binding to this property was dropped while its thread was
+    /// panicking, or if a validator or observer panicked while a binding committed. Poisoned
+    /// properties can't be bound until [clear_poison](Property::clear_poison) is called.
+    pub fn is_poisoned(&self) -> bool {
+        self.mut_lock.is_poisoned()
+    }
+
+    /// Clears the poisoned state, so the property can be bound again. It's up to the caller to
+    /// make sure the value is usable, e.g. by resetting it right after.
+    pub fn clear_poison(&self) {
+        self.mut_lock.clear_poison();
+    }
+
+    /// Consumes the property and returns its value. Works even if the property is poisoned.
+    pub fn into_inner(self) -> T {
+        self.property.into_inner()
+    }
+
+    /// Returns a mutable reference to the value. Since this takes `&mut self`, the property can't
+    /// be bound anywhere, so the lock isn't needed. Changes made this way skip validators and
+    /// observers. Works even if the property is poisoned.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.property.get_mut()
+    }
+
+    /// Sets the value, binding the property just long enough to do so. The change is committed
+    /// like dropping a [PropertyBinding] would, so it's validated and observed as usual.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is already bound, like [bind](Property::bind).
+    #[track_caller]
+    pub fn set(&self, value: T) {
+        *self.bind() = value;
+    }
+
+    /// Non-panicking version of [set](Property::set). The value is dropped if the property can't
+    /// be bound.
+    #[track_caller]
+    pub fn try_set(&self, value: T) -> Result<(), BindError> {
+        *self.try_bind()? = value;
+        Ok(())
+    }
+
+    /// Sets the value and returns the previous one. See [set](Property::set).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is already bound, like [bind](Property::bind).
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.bind(), value)
+    }
+
+    /// Non-panicking version of [replace](Property::replace).
+    #[track_caller]
+    pub fn try_replace(&self, value: T) -> Result<T, BindError> {
+        Ok(std::mem::replace(&mut *self.try_bind()?, value))
+    }
+
+    /// Changes the value with a closure and returns what the closure returned. See
+    /// [set](Property::set).
+    ///
+    /// ```rust
+    /// let p = binder::Property::new(vec![1, 2]);
+    /// assert_eq!(p.update(|v| { v.push(3); v.len() }), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is already bound, like [bind](Property::bind).
+    #[track_caller]
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut *self.bind())
+    }
+
+    /// Non-panicking version of [update](Property::update). The closure isn't called if the
+    /// property can't be bound.
+    #[track_caller]
+    pub fn try_update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, BindError> {
+        Ok(f(&mut *self.try_bind()?))
+    }
+
+    /// Swaps the values of two properties. Both are bound before either is changed, and both
+    /// changes are committed as if they were set. Swapping a property with itself does nothing.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if either property is already bound, like [bind](Property::bind). Neither
+    /// property is changed or bound at that point.
+    #[track_caller]
+    pub fn swap(&self, other: &Property<T>) {
+        if let Err(e) = self.try_swap(other) {
+            bind_panic::<T>("PropertyBinding", "already bound", e)
+        }
+    }
+
+    /// Non-panicking version of [swap](Property::swap). Neither property is changed if either one
+    /// can't be bound.
+    #[track_caller]
+    pub fn try_swap(&self, other: &Property<T>) -> Result<(), BindError> {
+        if !std::ptr::eq(self, other) {
+            let (mut a, mut b) = try_bind_all((self, other))?;
+            std::mem::swap(&mut *a, &mut *b);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Clone> Property<T> {
+    /// Returns a clone of the value, binding the property for reading just long enough to do so.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is bound mutably, like [bind_ref](Property::bind_ref).
+    #[track_caller]
+    pub fn get(&self) -> T {
+        self.bind_ref().clone()
+    }
+
+    /// Non-panicking version of [get](Property::get).
+    #[track_caller]
+    pub fn try_get(&self) -> Result<T, BindError> {
+        Ok(self.try_bind_ref()?.clone())
+    }
+
+    /// Non-blocking, non-panicking version of [clone](Clone::clone). Returns
+    /// [Err](core::result::Result::Err)([BindError]) right away if the property is bound mutably
+    /// or is poisoned, like [try_bind_ref](Property::try_bind_ref).
+    #[track_caller]
+    pub fn try_clone(&self) -> Result<Self, BindError> {
+        self.try_get().map(Property::new)
+    }
+}
+
+impl<T: Clone> Clone for Property<T> {
+    /// Creates a new property holding a clone of the value. The new property is independent of
+    /// this one, and doesn't share its observers or validators.
+    ///
+    /// If the property is bound mutably on another thread, the calling thread is parked until
+    /// that binding is dropped, so a property can be cloned while others are using it. Use
+    /// [try_clone](Property::try_clone) to get an error instead.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is poisoned, or if it seems to be bound mutably by the
+    /// calling thread, since waiting would never end (see [BindError::WouldDeadlock] for the
+    /// limits of that check).
+    #[track_caller]
+    fn clone(&self) -> Self {
+        match self.mut_lock.read_blocking(Location::caller()) {
+            Ok(ticket) => Property::new(T::clone(&self.read_binding(ticket))),
+            Err(e) => bind_panic::<T>("PropertyReadBinding", "already bound mutably", e)
+        }
+    }
+}
+
+impl<T: Default> Default for Property<T> {
+    fn default() -> Self {
+        Property::new(T::default())
+    }
+}
+
+impl<T> From<T> for Property<T> {
+    fn from(value: T) -> Self {
+        Property::new(value)
+    }
+}
+
+impl<T: Default> Property<T> {
+    /// Takes the value, leaving `T::default()` in its place. See [set](Property::set).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is already bound, like [bind](Property::bind).
+    #[track_caller]
+    pub fn take(&self) -> T {
+        std::mem::take(&mut *self.bind())
+    }
+
+    /// Non-panicking version of [take](Property::take).
+    #[track_caller]
+    pub fn try_take(&self) -> Result<T, BindError> {
+        Ok(std::mem::take(&mut *self.try_bind()?))
+    }
+}
+
+impl<T: Clone + 'static> Property<T> {
+    /// Registers an observer that's called with the old and new value whenever a
+    /// [PropertyBinding] that was mutably dereferenced is dropped. The observer is called on
+    /// the thread that dropped the binding, before the property is unbound, so it can't bind
+    /// this property itself.
+    ///
+    /// The observer stays registered until the returned [Subscription] is dropped.
+    pub fn subscribe<F>(&self, observer: F) -> Subscription
+        where F: Fn(&T, &T) + Send + Sync + 'static
+    {
+        self.hooks.subscribe(Arc::new(observer))
+    }
+}
+
+/// What a validator does with a value it doesn't accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationPolicy {
+    /// Roll back to the value from before the property was bound.
+    Reject,
+    /// Replace it with the closest valid value.
+    Coerce,
+}
+
+impl<T: Clone + 'static> Property<T> {
+    /// Adds a validator that's run on the new value whenever a [PropertyBinding] commits a
+    /// change. If it returns an error, the change is rejected: the value is rolled back to what
+    /// it was before the property was bound, and observers aren't notified.
+    ///
+    /// Validators run in the order they were added, before observers.
+    ///
+    /// ```rust
+    /// let p = binder::Property::new(2i32)
+    ///     .with_validator(|v| if v % 2 == 0 { Ok(()) } else { Err(format!("{} is odd", v)) });
+    /// let mut b = p.bind();
+    /// *b = 3;
+    /// assert_eq!(b.commit().unwrap_err().message(), "3 is odd");
+    /// assert_eq!(*p.bind_ref(), 2);
+    /// ```
+    pub fn with_validator<F>(self, validator: F) -> Self
+        where F: Fn(&T) -> Result<(), String> + Send + Sync + 'static
+    {
+        self.hooks.add_validator(Validator::Reject(Arc::new(validator)), Some(T::clone));
+        self
+    }
+}
+
+impl<T: PartialOrd + Clone + std::fmt::Debug + Send + Sync + 'static> Property<T> {
+    /// Adds a validator that keeps the value within `range`, either by rejecting values outside
+    /// of it or by clamping them to its bounds, depending on `policy`. Values that can't be
+    /// compared with the bounds, like `NaN`, are rejected or replaced with the lower bound.
+    ///
+    /// ```rust
+    /// use binder::ValidationPolicy;
+    /// let p = binder::Property::new(0.5f32).with_range(0.0..=1.0, ValidationPolicy::Coerce);
+    /// *p.bind() = 1.5;
+    /// assert_eq!(*p.bind_ref(), 1.0);
+    /// ```
+    pub fn with_range(self, range: RangeInclusive<T>, policy: ValidationPolicy) -> Self {
+        match policy {
+            ValidationPolicy::Reject => self.with_validator(move |v| {
+                if range.contains(v) { Ok(()) }
+                else { Err(format!("{:?} is outside of {:?}", v, range)) }
+            }),
+            ValidationPolicy::Coerce => self.with_coercion(move |v| {
+                // values that can't be compared with the bounds at all, like NaN, count as too low
+                if (*v).partial_cmp(range.start()).is_none_or(|o| o.is_lt()) { *v = range.start().clone(); }
+                else if *v > *range.end() { *v = range.end().clone(); }
             })
         }
     }
 }
 
+impl<T: 'static> Property<T> {
+    /// Adds a validator that fixes up the new value in place whenever a [PropertyBinding] commits
+    /// a change, e.g. to clamp or round it. Observers see the fixed-up value.
+    pub fn with_coercion<F>(self, coercion: F) -> Self
+        where F: Fn(&mut T) + Send + Sync + 'static
+    {
+        self.hooks.add_validator(Validator::Coerce(Arc::new(coercion)), None);
+        self
+    }
+
+    /// Binds a property shared through an [Arc](std::sync::Arc). The returned
+    /// [ArcPropertyBinding] owns a clone of the `Arc`, so unlike [bind](Property::bind) it isn't
+    /// tied to a borrow of the property.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is already bound.
+    #[track_caller]
+    pub fn bind_arc(self: &Arc<Self>) -> ArcPropertyBinding<T> {
+        let binding = self.bind();
+        // the binding can be sent anywhere, so it doesn't belong to this thread
+        self.mut_lock.disown();
+        // SAFETY: the binding only points into the `Arc`'s allocation, which is kept alive by the
+        // clone stored next to it and dropped after it.
+        let binding = unsafe { std::mem::transmute::<PropertyBinding<'_, T>, PropertyBinding<'static, T>>(binding) };
+        ArcPropertyBinding { binding, property: self.clone() }
+    }
+
+    /// Safer alternative to [bind_arc](Property::bind_arc). Fails under the same conditions as
+    /// [try_bind](Property::try_bind).
+    #[track_caller]
+    pub fn try_bind_arc(self: &Arc<Self>) -> Result<ArcPropertyBinding<T>, BindError> {
+        let binding = self.try_bind()?;
+        self.mut_lock.disown();
+        // SAFETY: see `bind_arc`
+        let binding = unsafe { std::mem::transmute::<PropertyBinding<'_, T>, PropertyBinding<'static, T>>(binding) };
+        Ok(ArcPropertyBinding { binding, property: self.clone() })
+    }
+}
+
+/// Panics with a message describing why a property couldn't be bound.
+#[track_caller]
+fn bind_panic<T>(binding: &str, contended: &str, error: BindError) -> ! {
+    match error {
+        BindError::Poisoned => panic!("{}<{}>: Tried to bind a poisoned property!", binding, std::any::type_name::<T>()),
+        e => {
+            let holder = e.holder().map(|h| format!(" ({})", h)).unwrap_or_default();
+            panic!("{}<{}>: Tried to bind a property that was {}!{}", binding, std::any::type_name::<T>(), contended, holder)
+        }
+    }
+}
+
 unsafe impl<T: Send> Send for Property<T> {}
 unsafe impl<T: Send + Sync> Sync for Property<T> {}
+// poisoning makes it safe to keep using a property after a panic, same as `Mutex`
+impl<T> std::panic::UnwindSafe for Property<T> {}
+impl<T> std::panic::RefUnwindSafe for Property<T> {}
 
 
 // hack to run compile_fail doctests
-// #[cfg(doc)]
-// #[doc(hidden)]
-// #[path = "../tests/tests.rs"]
-// mod tests;
+#[cfg(doctest)]
+#[doc(hidden)]
+#[path = "../tests/tests.rs"]
+mod tests;
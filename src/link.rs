@@ -0,0 +1,188 @@
+//! Two-way links between properties.
+
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::{try_bind_all, BindError, LinkError, Property, Subscription};
+
+
+/// An edge in the global link graph, between the addresses of two properties.
+struct Edge {
+    id: u64,
+    a: usize,
+    b: usize,
+}
+
+static LINKS: Mutex<Vec<Edge>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns `true` if `to` can be reached from `from` through existing links.
+fn connected(edges: &[Edge], from: usize, to: usize) -> bool {
+    let mut visited = vec![from];
+    let mut stack = vec![from];
+    while let Some(node) = stack.pop() {
+        if node == to {
+            return true;
+        }
+        for edge in edges {
+            let next = if edge.a == node { edge.b } else if edge.b == node { edge.a } else { continue };
+            if !visited.contains(&next) {
+                visited.push(next);
+                stack.push(next);
+            }
+        }
+    }
+    false
+}
+
+thread_local! {
+    /// Links that are copying a change on this thread, so the copy isn't copied back.
+    static PROPAGATING: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Copies the value of `from` into `to` after a change to `from` was committed and `from` was
+/// unbound.
+struct Propagate<T> {
+    id: u64,
+    from: Weak<Property<T>>,
+    to: Weak<Property<T>>,
+    /// Cleared when the [Link] is dropped, since a retry may still be waiting after that.
+    linked: Arc<AtomicBool>,
+}
+
+impl<T: Clone + Send + Sync + 'static> Propagate<T> {
+    fn run(self: &Arc<Self>) {
+        if !self.linked.load(Ordering::Acquire) || PROPAGATING.with(|p| p.borrow().contains(&self.id)) {
+            // unlinked, or `to` committing the copy made by this link
+            return;
+        }
+        let (Some(from), Some(to)) = (self.from.upgrade(), self.to.upgrade()) else { return };
+        PROPAGATING.with(|p| p.borrow_mut().push(self.id));
+        loop {
+            // both are bound at once, in the same order on every thread, and the latest value is
+            // copied rather than the committed one, so changes committed to both properties at
+            // the same time still leave them holding the same value
+            match try_bind_all((&*from, &*to)) {
+                Ok((from, mut to)) => {
+                    *to = from.clone();
+                    // unbound first, so `to`'s observers can bind it
+                    drop(from);
+                    break;
+                }
+                Err(BindError::AlreadyBound(_)) | Err(BindError::WouldDeadlock(_)) => {
+                    // try again once whatever is in the way is unbound, which may be on this
+                    // thread, so this can't wait for it. If neither is bound anymore, try again
+                    // right away.
+                    let retry = self.clone();
+                    let retry: Arc<dyn Fn() + Send + Sync> = Arc::new(move || retry.run());
+                    if from.mut_lock.when_unbound(retry.clone()) || to.mut_lock.when_unbound(retry) {
+                        break;
+                    }
+                }
+                // poisoned, which needs to be dealt with before anything else happens to it
+                Err(_) => break
+            }
+        }
+        PROPAGATING.with(|p| p.borrow_mut().retain(|i| *i != self.id));
+    }
+}
+
+fn keep_alive<T: Send + Sync + 'static>(property: &Arc<Property<T>>) -> Weak<dyn Any + Send + Sync> {
+    Arc::downgrade(property) as Weak<dyn Any + Send + Sync>
+}
+
+/// Handle to a two-way link created by [link]. The properties are unlinked when it's
+/// [Drop](std::ops::Drop)ped.
+#[must_use = "the properties are unlinked as soon as the Link is dropped"]
+pub struct Link {
+    id: u64,
+    linked: Arc<AtomicBool>,
+    _subscriptions: [Subscription; 2],
+    /// Keeps both properties' allocations (not their values) around, so their addresses can't be
+    /// reused by other properties while the edge is in the graph.
+    _properties: [Weak<dyn Any + Send + Sync>; 2],
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        self.linked.store(false, Ordering::Release);
+        LINKS.lock().unwrap_or_else(PoisonError::into_inner).retain(|e| e.id != self.id);
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Link").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// Links two properties, so a change committed to either one is copied to the other. `b` is set
+/// to `a`'s current value right away, so they start out in sync.
+///
+/// A change is copied once the binding that committed it has unbound the property, by binding
+/// both properties with [try_bind_all](crate::try_bind_all) and copying the latest value across.
+/// The copy goes through the other property's own validators, observers and links. If either
+/// property is bound elsewhere at that point, even just for reading, the copy is made as soon as
+/// it's unbound instead, by whichever thread unbinds it. Until then, the two hold different
+/// values. Changes committed to both at the same time always leave them holding the same one of
+/// the two values.
+///
+/// If the other property's validators reject the copy, or it's poisoned, the two are left holding
+/// different values until the next change to either one. Fails with [LinkError::Validation] if
+/// `b`'s validators reject `a`'s value, in which case the properties aren't linked.
+///
+/// Links form an undirected graph. Links that would make a cycle in it are rejected with
+/// [LinkError::Cycle], which also rules out linking the same two properties twice.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// let panel = Arc::new(binder::Property::new(0.0f32));
+/// let model = Arc::new(binder::Property::new(1.0f32));
+/// let link = binder::link(&panel, &model).unwrap();
+/// *panel.bind() = 0.5;
+/// assert_eq!(*model.bind_ref(), 0.5);
+/// *model.bind() = 0.25;
+/// assert_eq!(*panel.bind_ref(), 0.25);
+/// drop(link);
+/// ```
+pub fn link<T>(a: &Arc<Property<T>>, b: &Arc<Property<T>>) -> Result<Link, LinkError>
+    where T: Clone + Send + Sync + 'static
+{
+    let (addr_a, addr_b) = (Arc::as_ptr(a) as usize, Arc::as_ptr(b) as usize);
+    if addr_a == addr_b {
+        return Err(LinkError::SameProperty);
+    }
+
+    let id = {
+        let mut links = LINKS.lock().unwrap_or_else(PoisonError::into_inner);
+        if connected(&links, addr_a, addr_b) {
+            return Err(LinkError::Cycle);
+        }
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        links.push(Edge { id, a: addr_a, b: addr_b });
+        id
+    };
+    // synced without holding `LINKS`, since committing runs `b`'s observers, which may link or
+    // unlink properties themselves
+    let synced = a.try_get().map_err(LinkError::from).and_then(|value| {
+        let mut binding = b.try_bind()?;
+        *binding = value;
+        binding.commit().map_err(LinkError::Validation)
+    });
+    if let Err(e) = synced {
+        LINKS.lock().unwrap_or_else(PoisonError::into_inner).retain(|e| e.id != id);
+        return Err(e);
+    }
+
+    let linked = Arc::new(AtomicBool::new(true));
+    let propagate = |from: &Arc<Property<T>>, to: &Arc<Property<T>>| {
+        let propagate = Arc::new(Propagate { id, from: Arc::downgrade(from), to: Arc::downgrade(to), linked: linked.clone() });
+        from.hooks.watch_unlocked(Arc::new(move || propagate.run()))
+    };
+    let subscriptions = [propagate(a, b), propagate(b, a)];
+    Ok(Link { id, linked, _subscriptions: subscriptions, _properties: [keep_alive(a), keep_alive(b)] })
+}
@@ -0,0 +1,424 @@
+//! Properties for single-threaded code, which don't need atomics.
+
+
+use std::cell::{Cell, OnceCell, RefCell, UnsafeCell};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::panic::Location;
+use std::rc::{Rc, Weak};
+
+use crate::{bind_panic, BindError, Holder, ValidationError};
+
+
+/// Borrow state of a property that's bound mutably. Any other value counts read bindings.
+const WRITER: usize = usize::MAX;
+
+type LocalObserver<T> = Rc<dyn Fn(&T, &T)>;
+type LocalRejectValidator<T> = Rc<dyn Fn(&T) -> Result<(), String>>;
+type LocalCoerceValidator<T> = Rc<dyn Fn(&mut T)>;
+
+enum LocalValidator<T> {
+    /// Rolls the value back if it returns an error.
+    Reject(LocalRejectValidator<T>),
+    /// Fixes up the value in place.
+    Coerce(LocalCoerceValidator<T>),
+}
+
+impl<T> Clone for LocalValidator<T> {
+    fn clone(&self) -> Self {
+        match self {
+            LocalValidator::Reject(v) => LocalValidator::Reject(v.clone()),
+            LocalValidator::Coerce(v) => LocalValidator::Coerce(v.clone()),
+        }
+    }
+}
+
+/// Single-threaded version of the hooks used by [Property](crate::Property).
+struct LocalHookList<T> {
+    next_id: u64,
+    /// Used to take a copy of the value before it's first mutated, once a hook needs it.
+    snapshot: Option<fn(&T) -> T>,
+    observers: Vec<(u64, LocalObserver<T>)>,
+    validators: Vec<LocalValidator<T>>,
+}
+
+/// A bindable property for single-threaded code, e.g. UI state that never leaves the UI thread.
+/// Works like [Property](crate::Property), with the same [bind](LocalProperty::bind),
+/// [bind_ref](LocalProperty::bind_ref), observer and validator API, but keeps track of its
+/// bindings with a plain [Cell] instead of an atomic lock. Observers and validators don't need to
+/// be [Send] or [Sync] either.
+///
+/// Since nothing is ever waited for, there's no blocking or async binding. Use the [Bindable]
+/// trait to write code that accepts either kind of property.
+///
+/// ```rust
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let p = binder::LocalProperty::new(1i32);
+/// let changes = Rc::new(Cell::new(0));
+/// let counter = changes.clone();
+/// let _sub = p.subscribe(move |_, _| counter.set(counter.get() + 1));
+/// *p.bind() += 1;
+/// assert!(p.try_bind_ref().is_ok());
+/// assert_eq!((*p.bind_ref(), changes.get()), (2, 1));
+/// ```
+///
+/// # Safety
+///
+/// Neither [Send] nor [Sync], so all of its bindings are on the same thread and the borrow flag
+/// doesn't need to be synchronized.
+///
+/// [Bindable]: crate::Bindable
+pub struct LocalProperty<T> {
+    value: UnsafeCell<T>,
+    /// `0` if unbound, [WRITER] if bound mutably, otherwise the number of read bindings.
+    borrow: Cell<usize>,
+    poisoned: Cell<bool>,
+    /// Where the current bindings were created, for error messages.
+    #[cfg(debug_assertions)]
+    holder: Cell<Option<&'static Location<'static>>>,
+    hooks: OnceCell<Rc<RefCell<LocalHookList<T>>>>,
+}
+
+impl<T> LocalProperty<T> {
+    /// Creates a new `LocalProperty` that owns the given value. Doesn't allocate.
+    pub const fn new(value: T) -> Self {
+        LocalProperty {
+            value: UnsafeCell::new(value),
+            borrow: Cell::new(0),
+            poisoned: Cell::new(false),
+            #[cfg(debug_assertions)]
+            holder: Cell::new(None),
+            hooks: OnceCell::new(),
+        }
+    }
+
+    /// The binding that's in the way of a new one, in debug builds.
+    fn holder(&self) -> Option<Holder> {
+        #[cfg(debug_assertions)]
+        {
+            self.holder.get().map(|location| Holder {
+                location,
+                thread: std::thread::current(),
+                mutable: self.borrow.get() == WRITER,
+            })
+        }
+        #[cfg(not(debug_assertions))]
+        None
+    }
+
+    fn set_holder(&self, _location: &'static Location<'static>) {
+        #[cfg(debug_assertions)]
+        self.holder.set(Some(_location));
+    }
+
+    /// Attempts to bind the property.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is already bound, or if it's poisoned.
+    #[track_caller]
+    pub fn bind(&self) -> LocalPropertyBinding<'_, T> {
+        match self.try_bind() {
+            Ok(binding) => binding,
+            Err(e) => bind_panic::<T>("LocalPropertyBinding", "already bound", e)
+        }
+    }
+
+    /// Safer alternative to [bind](LocalProperty::bind). Returns [BindError::AlreadyBound] if the
+    /// property was already bound, and [BindError::Poisoned] if it's poisoned.
+    #[track_caller]
+    pub fn try_bind(&self) -> Result<LocalPropertyBinding<'_, T>, BindError> {
+        if self.poisoned.get() {
+            return Err(BindError::Poisoned);
+        }
+        if self.borrow.get() != 0 {
+            return Err(BindError::AlreadyBound(self.holder()));
+        }
+        self.borrow.set(WRITER);
+        self.set_holder(Location::caller());
+        Ok(LocalPropertyBinding { property: self, old: None, dirty: false })
+    }
+
+    /// Binds the property for reading only. Any number of read bindings can exist at the same
+    /// time, but they exclude mutable bindings from [bind](LocalProperty::bind).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is bound mutably, or if it's poisoned.
+    #[track_caller]
+    pub fn bind_ref(&self) -> LocalPropertyReadBinding<'_, T> {
+        match self.try_bind_ref() {
+            Ok(binding) => binding,
+            Err(e) => bind_panic::<T>("LocalPropertyReadBinding", "already bound mutably", e)
+        }
+    }
+
+    /// Safer alternative to [bind_ref](LocalProperty::bind_ref). Returns
+    /// [Err](core::result::Result::Err)([BindError]) if the property was already bound mutably
+    /// or is poisoned.
+    #[track_caller]
+    pub fn try_bind_ref(&self) -> Result<LocalPropertyReadBinding<'_, T>, BindError> {
+        if self.poisoned.get() {
+            return Err(BindError::Poisoned);
+        }
+        match self.borrow.get() {
+            WRITER => Err(BindError::AlreadyBound(self.holder())),
+            readers => {
+                if readers == 0 {
+                    self.set_holder(Location::caller());
+                }
+                self.borrow.set(readers + 1);
+                Ok(LocalPropertyReadBinding { property: self })
+            }
+        }
+    }
+
+    /// Returns `true` if a mutable binding to this property was dropped during a panic, or if a
+    /// validator or observer panicked. See [Property::is_poisoned](crate::Property::is_poisoned).
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.get()
+    }
+
+    /// Clears the poisoned state, so the property can be bound again.
+    pub fn clear_poison(&self) {
+        self.poisoned.set(false);
+    }
+
+    /// Consumes the property and returns its value. Works even if the property is poisoned.
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    fn hooks(&self) -> &Rc<RefCell<LocalHookList<T>>> {
+        self.hooks.get_or_init(|| Rc::new(RefCell::new(LocalHookList {
+            next_id: 0,
+            snapshot: None,
+            observers: Vec::new(),
+            validators: Vec::new(),
+        })))
+    }
+
+    fn add_validator(&self, validator: LocalValidator<T>, snapshot: Option<fn(&T) -> T>) {
+        let mut hooks = self.hooks().borrow_mut();
+        if snapshot.is_some() {
+            hooks.snapshot = snapshot;
+        }
+        hooks.validators.push(validator);
+    }
+
+    /// Adds a validator that fixes up the new value in place whenever a binding commits a
+    /// change. See [Property::with_coercion](crate::Property::with_coercion).
+    pub fn with_coercion<F>(self, coercion: F) -> Self
+        where F: Fn(&mut T) + 'static
+    {
+        self.add_validator(LocalValidator::Coerce(Rc::new(coercion)), None);
+        self
+    }
+}
+
+impl<T: Clone + 'static> LocalProperty<T> {
+    /// Registers an observer that's called with the old and new value whenever a
+    /// [LocalPropertyBinding] that was mutably dereferenced is dropped. See
+    /// [Property::subscribe](crate::Property::subscribe).
+    pub fn subscribe<F>(&self, observer: F) -> LocalSubscription
+        where F: Fn(&T, &T) + 'static
+    {
+        let hooks = self.hooks();
+        let id = {
+            let mut list = hooks.borrow_mut();
+            list.snapshot = Some(T::clone);
+            let id = list.next_id;
+            list.next_id += 1;
+            list.observers.push((id, Rc::new(observer)));
+            id
+        };
+        let weak: Weak<RefCell<LocalHookList<T>>> = Rc::downgrade(hooks);
+        LocalSubscription {
+            unsubscribe: Some(Box::new(move || {
+                if let Some(hooks) = weak.upgrade() {
+                    hooks.borrow_mut().observers.retain(|(i, _)| *i != id);
+                }
+            }))
+        }
+    }
+
+    /// Adds a validator that's run on the new value whenever a binding commits a change. See
+    /// [Property::with_validator](crate::Property::with_validator).
+    pub fn with_validator<F>(self, validator: F) -> Self
+        where F: Fn(&T) -> Result<(), String> + 'static
+    {
+        self.add_validator(LocalValidator::Reject(Rc::new(validator)), Some(T::clone));
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LocalProperty<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("LocalProperty");
+        match self.borrow.get() {
+            WRITER => s.field("value", &format_args!("<bound>")),
+            // SAFETY: there's no mutable binding, and none can be created while this runs
+            _ => s.field("value", unsafe { &*self.value.get() }),
+        };
+        s.field("poisoned", &self.poisoned.get()).finish_non_exhaustive()
+    }
+}
+
+
+/// A mutable binding to a [LocalProperty], returned by [bind](LocalProperty::bind). Works just
+/// like a [PropertyBinding](crate::PropertyBinding): dropping it commits the change, running
+/// validators and then observers.
+pub struct LocalPropertyBinding<'a, T> {
+    property: &'a LocalProperty<T>,
+    /// The value from before the first mutable dereference, if any hooks need it.
+    old: Option<T>,
+    dirty: bool,
+}
+
+impl<'a, T> LocalPropertyBinding<'a, T> {
+    /// The value, without marking it as changed.
+    fn value_mut(&mut self) -> &mut T {
+        // SAFETY: the property is bound mutably by this binding only
+        unsafe { &mut *self.property.value.get() }
+    }
+
+    /// Validates and commits the change, notifying observers if it was accepted.
+    fn finish(&mut self) -> Result<(), ValidationError> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.dirty = false;
+        let old = self.old.take();
+        let Some(hooks) = self.property.hooks.get() else { return Ok(()) };
+        // copy the hooks first, so they can register more hooks while running
+        let validators = hooks.borrow().validators.clone();
+        for validator in validators {
+            let result = match validator {
+                LocalValidator::Reject(v) => v(self.value_mut()).map_err(ValidationError::new),
+                LocalValidator::Coerce(v) => { v(self.value_mut()); Ok(()) }
+            };
+            if let Err(e) = result {
+                if let Some(old) = old {
+                    *self.value_mut() = old;
+                }
+                return Err(e);
+            }
+        }
+        let observers: Vec<LocalObserver<T>> = hooks.borrow().observers.iter().map(|(_, o)| o.clone()).collect();
+        if let Some(old) = &old {
+            for observer in observers {
+                observer(old, self);
+            }
+        }
+        Ok(())
+    }
+
+    /// Unbinds the property, like dropping the binding would, and returns whether the change was
+    /// accepted by the property's validators. See [PropertyBinding::commit](crate::PropertyBinding::commit).
+    pub fn commit(mut self) -> Result<(), ValidationError> {
+        self.finish()
+    }
+}
+
+impl<'a, T> Deref for LocalPropertyBinding<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the property is bound mutably by this binding only
+        unsafe { &*self.property.value.get() }
+    }
+}
+
+impl<'a, T> DerefMut for LocalPropertyBinding<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        if !self.dirty {
+            self.dirty = true;
+            let snapshot = self.property.hooks.get().and_then(|hooks| hooks.borrow().snapshot);
+            self.old = snapshot.map(|f| f(&**self));
+        }
+        self.value_mut()
+    }
+}
+
+impl<'a, T> Drop for LocalPropertyBinding<'a, T> {
+    fn drop(&mut self) {
+        let _unborrow = Unborrow(self.property);
+        // if the thread is already panicking, the value may have been left half-modified, so
+        // don't commit it. `_unborrow` poisons the property in that case, and also if a validator
+        // or observer panics.
+        if !std::thread::panicking() {
+            let _ = self.finish();
+        }
+    }
+}
+
+/// Unbinds a [LocalPropertyBinding]'s property when dropped, even if its hooks panic. Poisons
+/// the property if that happens while the thread is panicking.
+struct Unborrow<'a, T>(&'a LocalProperty<T>);
+
+impl<'a, T> Drop for Unborrow<'a, T> {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.poisoned.set(true);
+        }
+        self.0.borrow.set(0);
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for LocalPropertyBinding<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalPropertyBinding").field("value", &**self).field("dirty", &self.dirty).finish_non_exhaustive()
+    }
+}
+
+/// A shared, read-only binding to a [LocalProperty], returned by
+/// [bind_ref](LocalProperty::bind_ref). Only [Deref](std::ops::Deref)s to `&T`.
+pub struct LocalPropertyReadBinding<'a, T> {
+    property: &'a LocalProperty<T>,
+}
+
+impl<'a, T> Deref for LocalPropertyReadBinding<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: the property can't be bound mutably while this binding exists
+        unsafe { &*self.property.value.get() }
+    }
+}
+
+impl<'a, T> Drop for LocalPropertyReadBinding<'a, T> {
+    fn drop(&mut self) {
+        self.property.borrow.set(self.property.borrow.get() - 1);
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for LocalPropertyReadBinding<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalPropertyReadBinding").field("value", &**self).finish()
+    }
+}
+
+
+/// Handle to an observer registered with [LocalProperty::subscribe]. The observer is
+/// unsubscribed when the `LocalSubscription` is [Drop](std::ops::Drop)ped, like a
+/// [Subscription](crate::Subscription).
+#[must_use = "the observer is unsubscribed as soon as the LocalSubscription is dropped"]
+pub struct LocalSubscription {
+    unsubscribe: Option<Box<dyn FnOnce()>>,
+}
+
+impl Drop for LocalSubscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            unsubscribe();
+        }
+    }
+}
+
+impl fmt::Debug for LocalSubscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSubscription").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,551 @@
+//! The reader/writer lock that guards a property's value.
+
+
+use std::panic::Location;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::Waker;
+use std::thread::Thread;
+use std::time::Instant;
+
+use crate::{BindError, Holder};
+
+
+/// Set while the property is bound mutably.
+const WRITER: usize = 1 << (usize::BITS - 1);
+/// Set while there are threads waiting for the exclusive lock. While it's set, the lock is never
+/// released outright, it's handed to the first waiter instead.
+const QUEUED: usize = 1 << (usize::BITS - 2);
+/// Set while there are callbacks in [UNBOUND] waiting for the lock to be released.
+const WATCHED: usize = 1 << (usize::BITS - 3);
+/// Set once a writer panicked. Unlike the other bits, it stays set while nobody holds the lock.
+const POISONED: usize = 1 << (usize::BITS - 4);
+/// The rest of the bits count shared bindings.
+const READERS: usize = !(WRITER | QUEUED | WATCHED | POISONED);
+
+/// Owner value for a mutable binding that isn't tied to the thread that created it.
+const NO_OWNER: usize = 0;
+
+thread_local! {
+    static THREAD_TOKEN: u8 = const { 0 };
+}
+
+/// A cheap identifier for the current thread. The address of a thread-local is unique among all
+/// running threads, and never zero.
+fn thread_token() -> usize {
+    THREAD_TOKEN.with(|t| t as *const u8 as usize)
+}
+
+/// Number of shards in each of the tables below. Most locks are never contended, so the waiters
+/// and holders are kept in tables keyed by the lock's address rather than in the lock itself,
+/// which keeps properties small. The tables are sharded so that unrelated locks rarely share one.
+const SHARDS: usize = 64;
+
+type Table<T> = [Mutex<Vec<(usize, T)>>; SHARDS];
+
+/// The shard of a table that holds the entries for `key`.
+fn shard<T>(table: &'static Table<T>, key: usize) -> MutexGuard<'static, Vec<(usize, T)>> {
+    // locks are at least 8 bytes apart, so the lowest bits don't tell them apart. The entries
+    // are never left half-updated, so poisoning doesn't matter.
+    table[(key >> 3) % SHARDS].lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Threads and tasks waiting for a lock, keyed by its address, in the order they arrived.
+static WAITERS: Table<Arc<Waiter>> = [const { Mutex::new(Vec::new()) }; SHARDS];
+
+/// Holders of each lock, keyed by its address, recorded for diagnostics in debug builds only.
+#[cfg(debug_assertions)]
+static HOLDERS: Table<(u64, Holder)> = [const { Mutex::new(Vec::new()) }; SHARDS];
+
+/// Identifies the next holder added to [HOLDERS].
+#[cfg(debug_assertions)]
+static NEXT_TICKET: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+type Callback = Arc<dyn Fn() + Send + Sync>;
+
+/// Callbacks registered with [BindLock::when_unbound], keyed by the address of the lock.
+static UNBOUND: Mutex<Vec<(usize, Callback)>> = Mutex::new(Vec::new());
+
+fn unbound() -> MutexGuard<'static, Vec<(usize, Callback)>> {
+    // the list is never left half-updated, so poisoning doesn't matter
+    UNBOUND.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The callbacks that were waiting for a lock that has just been released. They're called when
+/// this is dropped, so the caller can decide to do that after its own cleanup.
+#[must_use]
+#[derive(Default)]
+pub(crate) struct Released {
+    callbacks: Vec<Callback>,
+}
+
+impl Drop for Released {
+    fn drop(&mut self) {
+        for callback in self.callbacks.drain(..) {
+            if std::thread::panicking() {
+                // panicking again would abort
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback()));
+            }
+            else {
+                callback();
+            }
+        }
+    }
+}
+
+/// Identifies one binding's entry in the lock's holder diagnostics. Empty in release builds.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Ticket {
+    #[cfg(debug_assertions)]
+    id: u64,
+}
+
+#[derive(Debug)]
+enum Wake {
+    Thread(Thread),
+    Task(Mutex<Waker>),
+}
+
+/// A thread or async task waiting for the lock.
+#[derive(Debug)]
+pub(crate) struct Waiter {
+    /// Set once the lock has been handed to this waiter.
+    granted: AtomicBool,
+    /// Waiting for a shared lock rather than the exclusive one.
+    shared: bool,
+    wake: Wake,
+}
+
+impl Waiter {
+    /// A waiter that parks the current thread.
+    fn thread(shared: bool) -> Arc<Self> {
+        Arc::new(Waiter { granted: AtomicBool::new(false), shared, wake: Wake::Thread(std::thread::current()) })
+    }
+
+    /// A waiter for the exclusive lock that wakes an async task.
+    pub(crate) fn task(waker: &Waker) -> Arc<Self> {
+        Arc::new(Waiter { granted: AtomicBool::new(false), shared: false, wake: Wake::Task(Mutex::new(waker.clone())) })
+    }
+
+    pub(crate) fn is_granted(&self) -> bool {
+        self.granted.load(Ordering::Acquire)
+    }
+
+    /// Replaces the waker of a task waiter, since a task may be polled with a different one.
+    pub(crate) fn set_waker(&self, waker: &Waker) {
+        if let Wake::Task(w) = &self.wake {
+            let mut w = w.lock().unwrap_or_else(PoisonError::into_inner);
+            if !w.will_wake(waker) {
+                w.clone_from(waker);
+            }
+        }
+    }
+
+    fn grant(&self) {
+        self.granted.store(true, Ordering::Release);
+        match &self.wake {
+            Wake::Thread(thread) => thread.unpark(),
+            Wake::Task(waker) => waker.lock().unwrap_or_else(PoisonError::into_inner).wake_by_ref()
+        }
+    }
+}
+
+/// The waiters for one lock, in the shard of [WAITERS] that holds them.
+struct Queue {
+    key: usize,
+    shard: MutexGuard<'static, Vec<(usize, Arc<Waiter>)>>,
+}
+
+impl Queue {
+    fn front(&self) -> Option<&Arc<Waiter>> {
+        self.shard.iter().find(|(key, _)| *key == self.key).map(|(_, waiter)| waiter)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.front().is_none()
+    }
+
+    fn push_back(&mut self, waiter: Arc<Waiter>) {
+        self.shard.push((self.key, waiter));
+    }
+
+    fn pop_front(&mut self) -> Option<Arc<Waiter>> {
+        let i = self.shard.iter().position(|(key, _)| *key == self.key)?;
+        Some(self.shard.remove(i).1)
+    }
+
+    fn remove(&mut self, waiter: &Arc<Waiter>) {
+        let key = self.key;
+        self.shard.retain(|(k, w)| *k != key || !Arc::ptr_eq(w, waiter));
+    }
+}
+
+/// Lock state shared by a property and its bindings. Any number of readers XOR one writer.
+/// Threads waiting for the lock are queued and served in FIFO order.
+#[derive(Debug)]
+pub(crate) struct BindLock {
+    state: AtomicUsize,
+    /// [thread_token] of the thread holding the exclusive lock, if known.
+    owner: AtomicUsize,
+}
+
+impl BindLock {
+    pub(crate) const fn new() -> Self {
+        BindLock {
+            state: AtomicUsize::new(0),
+            owner: AtomicUsize::new(NO_OWNER),
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn add_holder(&self, location: &'static Location<'static>, mutable: bool) -> Ticket {
+        #[cfg(debug_assertions)]
+        {
+            let id = NEXT_TICKET.fetch_add(1, Ordering::Relaxed);
+            let thread = std::thread::current();
+            shard(&HOLDERS, self.key()).push((self.key(), (id, Holder { location, thread, mutable })));
+            Ticket { id }
+        }
+        #[cfg(not(debug_assertions))]
+        Ticket {}
+    }
+
+    #[allow(unused_variables)]
+    fn remove_holder(&self, ticket: Ticket) {
+        #[cfg(debug_assertions)]
+        shard(&HOLDERS, self.key()).retain(|(key, (id, _))| *key != self.key() || *id != ticket.id);
+    }
+
+    /// The most relevant current holder: the mutable one if there is one, otherwise the most
+    /// recent reader. Always `None` in release builds.
+    fn holder(&self) -> Option<Holder> {
+        #[cfg(debug_assertions)]
+        {
+            let holders = shard(&HOLDERS, self.key());
+            let mut holders = holders.iter().filter(|(key, _)| *key == self.key()).map(|(_, holder)| holder);
+            holders.clone().find(|(_, h)| h.mutable)
+                .or_else(|| holders.next_back())
+                .map(|(_, h)| h.clone())
+        }
+        #[cfg(not(debug_assertions))]
+        None
+    }
+
+    /// The error to report when the lock couldn't be taken.
+    fn contended(&self) -> BindError {
+        let owner = self.owner.load(Ordering::Relaxed);
+        if owner != NO_OWNER && owner == thread_token() {
+            BindError::WouldDeadlock(self.holder())
+        }
+        else {
+            BindError::AlreadyBound(self.holder())
+        }
+    }
+
+    pub(crate) fn is_poisoned(&self) -> bool {
+        self.state.load(Ordering::Relaxed) & POISONED != 0
+    }
+
+    /// Marks the lock as poisoned. Only called by a writer while it still holds the lock.
+    pub(crate) fn poison(&self) {
+        self.state.fetch_or(POISONED, Ordering::Relaxed);
+    }
+
+    pub(crate) fn clear_poison(&self) {
+        self.state.fetch_and(!POISONED, Ordering::Relaxed);
+    }
+
+    fn waiters(&self) -> Queue {
+        Queue { key: self.key(), shard: shard(&WAITERS, self.key()) }
+    }
+
+    fn key(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    /// Registers a callback that's called once, the next time nobody holds the lock, e.g. to try
+    /// something again that failed because the lock was held. Returns `false` without registering
+    /// it if nobody holds the lock right now.
+    pub(crate) fn when_unbound(&self, callback: Callback) -> bool {
+        let mut callbacks = unbound();
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & (WRITER | READERS) == 0 {
+                return false;
+            }
+            // the callbacks are only taken out once the bit is seen by whoever releases the lock,
+            // which has to wait for `callbacks` to be unlocked
+            match self.state.compare_exchange_weak(state, state | WATCHED, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(s) => state = s
+            }
+        }
+        callbacks.push((self.key(), callback));
+        true
+    }
+
+    /// The callbacks to call after releasing the lock from `state`.
+    fn released(&self, state: usize) -> Released {
+        if state & WATCHED == 0 {
+            return Released::default();
+        }
+        let mut callbacks = Vec::new();
+        unbound().retain(|(key, callback)| {
+            let waiting = *key == self.key();
+            if waiting {
+                callbacks.push(callback.clone());
+            }
+            !waiting
+        });
+        Released { callbacks }
+    }
+
+    /// Finishes taking the exclusive lock once the state says it's held.
+    pub(crate) fn acquired_write(&self, location: &'static Location<'static>) -> Result<Ticket, BindError> {
+        if self.is_poisoned() {
+            drop(self.release_write());
+            return Err(BindError::Poisoned);
+        }
+        self.owner.store(thread_token(), Ordering::Relaxed);
+        Ok(self.add_holder(location, true))
+    }
+
+    /// Takes the exclusive lock if nobody holds the lock at all and it isn't poisoned.
+    pub(crate) fn try_write(&self, location: &'static Location<'static>) -> Result<Ticket, BindError> {
+        match self.state.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => self.acquired_write(location),
+            Err(POISONED) => Err(BindError::Poisoned),
+            Err(_) => Err(self.contended())
+        }
+    }
+
+    /// Finishes taking a shared lock once it's been handed to a waiter.
+    fn acquired_read(&self, location: &'static Location<'static>) -> Result<Ticket, BindError> {
+        if self.is_poisoned() {
+            drop(self.release_read());
+            return Err(BindError::Poisoned);
+        }
+        Ok(self.add_holder(location, false))
+    }
+
+    /// Puts a waiter at the back of the queue. Returns `true` if the lock was released in the
+    /// meantime and taken right away instead, in which case the caller holds it and must finish
+    /// with [acquired_write](BindLock::acquired_write), or `acquired_read` for a shared waiter.
+    pub(crate) fn enqueue(&self, waiter: &Arc<Waiter>) -> bool {
+        let mut waiters = self.waiters();
+        if waiter.shared {
+            // readers only wait for the writer, not for other waiters
+            let mut state = self.state.load(Ordering::Relaxed);
+            loop {
+                let free = state & WRITER == 0 && state & READERS != READERS;
+                let new = if free { state + 1 } else { state | QUEUED };
+                match self.state.compare_exchange_weak(state, new, Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) if free => return true,
+                    Ok(_) => break,
+                    Err(s) => state = s
+                }
+            }
+            waiters.push_back(waiter.clone());
+            return false;
+        }
+        // from here on, the lock won't be released without checking the queue
+        let state = self.state.fetch_or(QUEUED, Ordering::Acquire) | QUEUED;
+        // the queue must be empty if nobody holds the lock, or it would've been handed off.
+        // Readers can still come in meanwhile, in which case the last one hands it off later.
+        if state & (WRITER | READERS) == 0
+            && self.state.compare_exchange(state, WRITER | (state & POISONED), Ordering::Acquire, Ordering::Relaxed).is_ok() {
+            return true;
+        }
+        waiters.push_back(waiter.clone());
+        false
+    }
+
+    /// Takes a waiter out of the queue. Returns `true` if the lock was already handed to it, in
+    /// which case the caller holds it and has to either use or release it.
+    pub(crate) fn dequeue(&self, waiter: &Arc<Waiter>) -> bool {
+        let mut waiters = self.waiters();
+        if waiter.is_granted() {
+            return true;
+        }
+        waiters.remove(waiter);
+        if waiters.is_empty() {
+            self.state.fetch_and(!QUEUED, Ordering::Relaxed);
+        }
+        false
+    }
+
+    /// Takes the exclusive lock, parking the thread until it's free or `deadline` passes.
+    pub(crate) fn write_blocking(&self, location: &'static Location<'static>, deadline: Option<Instant>) -> Result<Ticket, BindError> {
+        match self.try_write(location) {
+            // the owner is only a hint, since the binding may have been sent to another thread
+            // since, so wait either way
+            Err(BindError::AlreadyBound(_)) | Err(BindError::WouldDeadlock(_)) => {}
+            result => return result
+        }
+
+        let waiter = Waiter::thread(false);
+        if self.enqueue(&waiter) {
+            return self.acquired_write(location);
+        }
+        while !waiter.is_granted() {
+            match deadline {
+                None => std::thread::park(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now < deadline {
+                        std::thread::park_timeout(deadline - now);
+                    }
+                    else if !self.dequeue(&waiter) {
+                        return Err(BindError::TimedOut);
+                    }
+                }
+            }
+        }
+        self.acquired_write(location)
+    }
+
+    /// Takes a shared lock, parking the thread until nobody holds the exclusive lock. Unlike
+    /// [write_blocking](BindLock::write_blocking), this doesn't wait if the calling thread seems
+    /// to hold the exclusive lock, and returns [BindError::WouldDeadlock] instead.
+    pub(crate) fn read_blocking(&self, location: &'static Location<'static>) -> Result<Ticket, BindError> {
+        match self.try_read(location) {
+            Err(BindError::AlreadyBound(_)) => {}
+            result => return result
+        }
+
+        let waiter = Waiter::thread(true);
+        if self.enqueue(&waiter) {
+            return self.acquired_read(location);
+        }
+        while !waiter.is_granted() {
+            std::thread::park();
+        }
+        self.acquired_read(location)
+    }
+
+    /// Takes a shared lock if nobody holds the exclusive lock, and it isn't poisoned. Threads
+    /// waiting for the exclusive lock don't keep readers out, so a reader never fails while the
+    /// property isn't bound mutably.
+    pub(crate) fn try_read(&self, location: &'static Location<'static>) -> Result<Ticket, BindError> {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITER != 0 || state & READERS == READERS {
+                return Err(self.contended());
+            }
+            if self.is_poisoned() {
+                return Err(BindError::Poisoned);
+            }
+            match self.state.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => return Ok(self.add_holder(location, false)),
+                Err(s) => state = s
+            }
+        }
+    }
+
+    /// Forgets which thread holds the exclusive lock, for bindings that may be sent elsewhere.
+    pub(crate) fn disown(&self) {
+        self.owner.store(NO_OWNER, Ordering::Relaxed);
+    }
+
+    /// Gives the exclusive lock, which the caller holds, to the first waiter, or a shared lock to
+    /// every reader at the front of the queue. Releases it if there are no waiters left.
+    fn hand_off(&self) -> Released {
+        let mut waiters = self.waiters();
+        let mut granted = Vec::new();
+        match waiters.pop_front() {
+            Some(waiter) if waiter.shared => {
+                granted.push(waiter);
+                while waiters.front().is_some_and(|w| w.shared) {
+                    granted.extend(waiters.pop_front());
+                }
+            }
+            Some(waiter) => granted.push(waiter),
+            None => {}
+        }
+        let queued = if waiters.is_empty() { 0 } else { QUEUED };
+        let new = match granted.first() {
+            Some(waiter) if waiter.shared => granted.len(),
+            Some(_) => WRITER,
+            None => 0
+        };
+        // nothing else changes the state while the caller holds the exclusive lock, except for
+        // `when_unbound`. The lock is still held if it was handed off, so that keeps waiting.
+        let kept = if new == 0 { POISONED } else { WATCHED | POISONED };
+        let old = self.state.fetch_update(Ordering::Release, Ordering::Relaxed, |s| Some(new | queued | (s & kept))).unwrap();
+        for waiter in granted {
+            waiter.grant();
+        }
+        if new == 0 { self.released(old) } else { Released::default() }
+    }
+
+    /// Releases the exclusive lock, handing it off if there are waiters. Returns `None` if it
+    /// wasn't held.
+    pub(crate) fn release_write(&self) -> Option<Released> {
+        self.owner.store(NO_OWNER, Ordering::Relaxed);
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITER == 0 {
+                return None;
+            }
+            if state & QUEUED != 0 {
+                return Some(self.hand_off());
+            }
+            match self.state.compare_exchange_weak(state, state & POISONED, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) => return Some(self.released(state)),
+                Err(s) => state = s
+            }
+        }
+    }
+
+    /// Releases the exclusive lock. Returns `None` if it wasn't held.
+    pub(crate) fn unlock_write(&self, ticket: Ticket) -> Option<Released> {
+        self.remove_holder(ticket);
+        self.release_write()
+    }
+
+    /// Releases one shared lock. Returns `None` if none were held.
+    pub(crate) fn unlock_read(&self, ticket: Ticket) -> Option<Released> {
+        self.remove_holder(ticket);
+        self.release_read()
+    }
+
+    fn release_read(&self) -> Option<Released> {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITER != 0 || state & READERS == 0 {
+                return None;
+            }
+            let last = state & READERS == 1;
+            let new = if last && state & QUEUED != 0 {
+                // last reader out takes the exclusive lock just long enough to hand it off
+                WRITER | QUEUED | (state & (WATCHED | POISONED))
+            }
+            else if last {
+                state & POISONED
+            }
+            else {
+                state - 1
+            };
+            match self.state.compare_exchange_weak(state, new, Ordering::Release, Ordering::Relaxed) {
+                Ok(_) if new & WRITER != 0 => return Some(self.hand_off()),
+                Ok(_) if last => return Some(self.released(state)),
+                Ok(_) => return Some(Released::default()),
+                Err(s) => state = s
+            }
+        }
+    }
+}
+
+impl Drop for BindLock {
+    fn drop(&mut self) {
+        // a binding or future that was leaked can leave entries in the tables, which mustn't be
+        // mistaken for those of a lock that's later put at the same address
+        if *self.state.get_mut() & !POISONED != 0 {
+            let key = self.key();
+            shard(&WAITERS, key).retain(|(k, _)| *k != key);
+            #[cfg(debug_assertions)]
+            shard(&HOLDERS, key).retain(|(k, _)| *k != key);
+            unbound().retain(|(k, _)| *k != key);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Binding several properties at once.
+
+
+use std::panic::Location;
+
+use crate::{BindError, Property, PropertyBinding};
+
+
+/// A tuple of up to eight `&Property` references that can be bound together with [bind_all] or
+/// [try_bind_all]. The methods are implementation details.
+pub trait BindAll<'a> {
+    /// The tuple of bindings returned when every property was bound.
+    type Bindings;
+    #[doc(hidden)]
+    type Slots: Default;
+    #[doc(hidden)]
+    fn addresses(&self) -> Vec<usize>;
+    #[doc(hidden)]
+    fn bind_slot(&self, slots: &mut Self::Slots, index: usize, location: &'static Location<'static>, block: bool) -> Result<(), BindError>;
+    #[doc(hidden)]
+    fn finish(slots: Self::Slots) -> Self::Bindings;
+}
+
+macro_rules! impl_bind_all {
+    ($(($T:ident, $i:tt)),+) => {
+        impl<'a, $($T),+> BindAll<'a> for ($(&'a Property<$T>,)+) {
+            type Bindings = ($(PropertyBinding<'a, $T>,)+);
+            type Slots = ($(Option<PropertyBinding<'a, $T>>,)+);
+
+            fn addresses(&self) -> Vec<usize> {
+                vec![$(self.$i as *const Property<$T> as usize),+]
+            }
+
+            fn bind_slot(&self, slots: &mut Self::Slots, index: usize, location: &'static Location<'static>, block: bool) -> Result<(), BindError> {
+                match index {
+                    $($i => {
+                        let lock = &self.$i.mut_lock;
+                        let ticket = if block { lock.write_blocking(location, None)? } else { lock.try_write(location)? };
+                        slots.$i = Some(self.$i.binding(ticket));
+                    })+
+                    _ => unreachable!()
+                }
+                Ok(())
+            }
+
+            fn finish(slots: Self::Slots) -> Self::Bindings {
+                ($(slots.$i.unwrap(),)+)
+            }
+        }
+    };
+}
+
+impl_bind_all!((A, 0));
+impl_bind_all!((A, 0), (B, 1));
+impl_bind_all!((A, 0), (B, 1), (C, 2));
+impl_bind_all!((A, 0), (B, 1), (C, 2), (D, 3));
+impl_bind_all!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4));
+impl_bind_all!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5));
+impl_bind_all!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5), (G, 6));
+impl_bind_all!((A, 0), (B, 1), (C, 2), (D, 3), (E, 4), (F, 5), (G, 6), (H, 7));
+
+/// The order to bind properties in, which is the same for every caller: by address.
+fn lock_order(addresses: &[usize]) -> Result<Vec<usize>, BindError> {
+    let mut order: Vec<usize> = (0..addresses.len()).collect();
+    order.sort_by_key(|&i| addresses[i]);
+    if order.windows(2).any(|w| addresses[w[0]] == addresses[w[1]]) {
+        // binding the same property twice could never succeed
+        return Err(BindError::WouldDeadlock(None));
+    }
+    Ok(order)
+}
+
+/// Binds every property in a tuple, or none of them. Properties are always bound in the same
+/// global order, by address, so two threads binding overlapping sets can't deadlock.
+///
+/// Returns [BindError::AlreadyBound] if any of the properties is already bound. Any bindings
+/// made before finding that out are released again.
+///
+/// ```rust
+/// let min = binder::Property::new(0.0f32);
+/// let max = binder::Property::new(1.0f32);
+/// let (mut min, mut max) = binder::try_bind_all((&min, &max)).unwrap();
+/// *min = 0.25;
+/// *max = 0.75;
+/// ```
+#[track_caller]
+pub fn try_bind_all<'a, S: BindAll<'a>>(properties: S) -> Result<S::Bindings, BindError> {
+    let location = Location::caller();
+    let mut slots = S::Slots::default();
+    for i in lock_order(&properties.addresses())? {
+        properties.bind_slot(&mut slots, i, location, false)?;
+    }
+    Ok(S::finish(slots))
+}
+
+/// Blocking version of [try_bind_all]. If any property is already bound, every binding made so
+/// far is released and the thread waits (like [bind_blocking](Property::bind_blocking)) for the
+/// property that was in the way. Once it's free, the rest are tried again, so the thread never
+/// waits while holding on to other properties.
+///
+/// Returns [BindError::WouldDeadlock] if the same property is passed twice, and
+/// [BindError::Poisoned] if one of them is poisoned. Waiting for a property that's bound by the
+/// calling thread blocks forever.
+#[track_caller]
+pub fn bind_all<'a, S: BindAll<'a>>(properties: S) -> Result<S::Bindings, BindError> {
+    let location = Location::caller();
+    let order = lock_order(&properties.addresses())?;
+    let mut wait_for = None;
+    'retry: loop {
+        let mut slots = S::Slots::default();
+        if let Some(i) = wait_for {
+            properties.bind_slot(&mut slots, i, location, true)?;
+        }
+        for &i in &order {
+            if Some(i) == wait_for {
+                continue;
+            }
+            match properties.bind_slot(&mut slots, i, location, false) {
+                Ok(()) => {}
+                Err(BindError::AlreadyBound(_)) | Err(BindError::WouldDeadlock(_)) => {
+                    // back off: release everything, then wait for this one
+                    wait_for = Some(i);
+                    continue 'retry;
+                }
+                Err(e) => return Err(e)
+            }
+        }
+        return Ok(S::finish(slots));
+    }
+}
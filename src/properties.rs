@@ -0,0 +1,110 @@
+//! Structs made of properties, usually implemented with `#[derive(Properties)]`.
+
+
+use std::ops::RangeInclusive;
+
+use crate::Property;
+
+
+/// Metadata about one [Property] field of a struct implementing [Properties].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldInfo {
+    name: &'static str,
+    doc: &'static str,
+    range: Option<(f64, f64)>,
+}
+
+impl FieldInfo {
+    /// Creates the metadata for a field. Only needed when implementing [Reflect](crate::Reflect)
+    /// by hand, since `#[derive(Properties)]` generates it.
+    pub const fn new(name: &'static str, doc: &'static str, range: Option<(f64, f64)>) -> Self {
+        FieldInfo { name, doc, range }
+    }
+
+    /// The name of the field.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The field's doc comment, without the leading `///`. Empty if it has none.
+    pub fn doc(&self) -> &'static str {
+        self.doc
+    }
+
+    /// The range given with `#[property(range = min..=max)]`, if any. It's only a hint for
+    /// inspectors: values outside of it aren't rejected unless the property has a validator, e.g.
+    /// one added with [with_range](Property::with_range).
+    pub fn range(&self) -> Option<RangeInclusive<f64>> {
+        self.range.map(|(min, max)| min..=max)
+    }
+}
+
+/// Visits every [Property] field of a struct implementing [Properties], in declaration order.
+/// `'a` is the lifetime of the borrow of the struct, so a visitor can keep the properties it's
+/// given, e.g. to collect them into a list. Visitors that don't need to can implement it for
+/// every `'a`.
+pub trait FieldVisitor<'a> {
+    /// Called for each field, with its metadata and the property itself.
+    fn visit<T: 'static>(&mut self, info: &'static FieldInfo, property: &'a Property<T>);
+}
+
+/// A struct whose fields are [Property]s. Usually implemented with `#[derive(Properties)]`,
+/// which needs the `derive` feature.
+///
+/// The derive looks at every field whose type is named `Property<T>`, and ignores the rest. It
+/// also generates a plain-data `{Name}Snapshot` struct, with a field of type `T` for each
+/// property, which derives [Clone]. Every `T` has to be [Clone], since
+/// [snapshot](Properties::snapshot) clones the values. The snapshot only has the generic
+/// parameters that the properties' types use.
+///
+/// Doc comments on the fields end up in [FieldInfo::doc], and a field can be given a range for
+/// inspectors with `#[property(range = min..=max)]`. Marking the struct with
+/// `#[properties(reflect)]` also implements [Reflect](crate::Reflect) for it.
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// use binder::{Properties, Property};
+///
+/// #[derive(Properties)]
+/// struct Light {
+///     /// Brightness, from off to fully on.
+///     #[property(range = 0.0..=1.0)]
+///     intensity: Property<f32>,
+///     name: Property<String>,
+/// }
+///
+/// let light = Light { intensity: Property::new(0.5), name: Property::new(String::from("sun")) };
+/// let before = light.snapshot();
+/// *light.intensity.bind() = 1.0;
+/// light.restore(before);
+/// assert_eq!(*light.intensity.bind_ref(), 0.5);
+/// assert_eq!(Light::FIELDS[0].doc(), "Brightness, from off to fully on.");
+/// assert_eq!(Light::FIELDS[0].range(), Some(0.0..=1.0));
+/// # }
+/// ```
+pub trait Properties {
+    /// The plain-data copy of every property's value.
+    type Snapshot;
+
+    /// Metadata about every property field, in declaration order.
+    const FIELDS: &'static [FieldInfo];
+
+    /// Copies every property's value.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if any of the properties is bound mutably, like
+    /// [bind_ref](Property::bind_ref).
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Sets every property to the value from a snapshot. Each property is bound, and its
+    /// change committed, one after the other.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if any of the properties is already bound, like [bind](Property::bind).
+    fn restore(&self, snapshot: Self::Snapshot);
+
+    /// Calls the visitor for every property field, in declaration order.
+    fn visit_fields<'a, V: FieldVisitor<'a>>(&'a self, visitor: &mut V);
+}
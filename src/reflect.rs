@@ -0,0 +1,224 @@
+//! Visiting and binding properties without knowing their types.
+
+
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use crate::{bind_panic, BindError, FieldInfo, Property, PropertyBinding, PropertyReadBinding, ValidationError};
+
+
+/// A [Property] with its value type erased, so properties of different types can be handled the
+/// same way, e.g. by an inspector that finds them through [Reflect]. Implemented for every
+/// `Property<T>` where `T: 'static`.
+///
+/// The bindings returned from here work just like the typed ones, and dereference to
+/// [`dyn Any`](std::any::Any), which can be downcast to the actual type.
+pub trait AnyProperty {
+    /// The name of the value's type, from [std::any::type_name].
+    fn type_name(&self) -> &'static str;
+
+    /// The [TypeId] of the value's type.
+    fn value_type_id(&self) -> TypeId;
+
+    /// Type-erased [bind](Property::bind).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called while the property is already bound.
+    fn bind_any(&self) -> AnyPropertyBinding<'_>;
+
+    /// Type-erased [try_bind](Property::try_bind).
+    fn try_bind_any(&self) -> Result<AnyPropertyBinding<'_>, BindError>;
+
+    /// Type-erased [bind_ref](Property::bind_ref).
+    ///
+    /// # Panics
+    ///
+    /// This will panic if the property is bound mutably.
+    fn bind_ref_any(&self) -> AnyPropertyReadBinding<'_>;
+
+    /// Type-erased [try_bind_ref](Property::try_bind_ref).
+    fn try_bind_ref_any(&self) -> Result<AnyPropertyReadBinding<'_>, BindError>;
+}
+
+impl<T: 'static> AnyProperty for Property<T> {
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    #[track_caller]
+    fn bind_any(&self) -> AnyPropertyBinding<'_> {
+        match self.try_bind_any() {
+            Ok(binding) => binding,
+            Err(e) => bind_panic::<T>("PropertyBinding", "already bound", e)
+        }
+    }
+
+    #[track_caller]
+    fn try_bind_any(&self) -> Result<AnyPropertyBinding<'_>, BindError> {
+        Ok(AnyPropertyBinding { binding: Box::new(self.try_bind()?), type_name: self.type_name() })
+    }
+
+    #[track_caller]
+    fn bind_ref_any(&self) -> AnyPropertyReadBinding<'_> {
+        match self.try_bind_ref_any() {
+            Ok(binding) => binding,
+            Err(e) => bind_panic::<T>("PropertyReadBinding", "already bound mutably", e)
+        }
+    }
+
+    #[track_caller]
+    fn try_bind_ref_any(&self) -> Result<AnyPropertyReadBinding<'_>, BindError> {
+        Ok(AnyPropertyReadBinding { binding: Box::new(self.try_bind_ref()?), type_name: self.type_name() })
+    }
+}
+
+/// Type-erased [PropertyBinding].
+trait ErasedBinding {
+    fn get(&self) -> &dyn Any;
+    fn get_mut(&mut self) -> &mut dyn Any;
+    fn commit(self: Box<Self>) -> Result<(), ValidationError>;
+}
+
+impl<'a, T: 'static> ErasedBinding for PropertyBinding<'a, T> {
+    fn get(&self) -> &dyn Any {
+        &**self
+    }
+
+    fn get_mut(&mut self) -> &mut dyn Any {
+        &mut **self
+    }
+
+    fn commit(self: Box<Self>) -> Result<(), ValidationError> {
+        (*self).commit()
+    }
+}
+
+/// A [PropertyBinding] with its value type erased, returned by [AnyProperty::bind_any].
+/// Dereferences to [`dyn Any`](std::any::Any). Mutably dereferencing it marks the value as
+/// changed, even if it's only downcast, so validators and observers run when it's dropped.
+pub struct AnyPropertyBinding<'a> {
+    binding: Box<dyn ErasedBinding + 'a>,
+    type_name: &'static str,
+}
+
+impl<'a> AnyPropertyBinding<'a> {
+    /// See [PropertyBinding::commit].
+    pub fn commit(self) -> Result<(), ValidationError> {
+        self.binding.commit()
+    }
+}
+
+impl<'a> Deref for AnyPropertyBinding<'a> {
+    type Target = dyn Any;
+
+    fn deref(&self) -> &Self::Target {
+        self.binding.get()
+    }
+}
+
+impl<'a> DerefMut for AnyPropertyBinding<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.binding.get_mut()
+    }
+}
+
+impl<'a> fmt::Debug for AnyPropertyBinding<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyPropertyBinding").field("type_name", &self.type_name).finish_non_exhaustive()
+    }
+}
+
+/// Type-erased [PropertyReadBinding].
+trait ErasedReadBinding {
+    fn get(&self) -> &dyn Any;
+}
+
+impl<'a, T: 'static> ErasedReadBinding for PropertyReadBinding<'a, T> {
+    fn get(&self) -> &dyn Any {
+        &**self
+    }
+}
+
+/// A [PropertyReadBinding] with its value type erased, returned by
+/// [AnyProperty::bind_ref_any]. Dereferences to [`dyn Any`](std::any::Any).
+pub struct AnyPropertyReadBinding<'a> {
+    binding: Box<dyn ErasedReadBinding + 'a>,
+    type_name: &'static str,
+}
+
+impl<'a> Deref for AnyPropertyReadBinding<'a> {
+    type Target = dyn Any;
+
+    fn deref(&self) -> &Self::Target {
+        self.binding.get()
+    }
+}
+
+impl<'a> fmt::Debug for AnyPropertyReadBinding<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyPropertyReadBinding").field("type_name", &self.type_name).finish_non_exhaustive()
+    }
+}
+
+/// Visits the properties of a value implementing [Reflect]. `'a` is the lifetime of the borrow
+/// of that value, so the visitor can hold on to the properties it's given.
+pub trait PropertyVisitor<'a> {
+    /// Called for each property, with its metadata.
+    fn visit(&mut self, info: &FieldInfo, property: &'a dyn AnyProperty);
+}
+
+/// Something that contains properties which can be enumerated at runtime, e.g. by a generic
+/// inspector, serializer or diff tool. `#[derive(Properties)]` implements it when the struct is
+/// marked with `#[properties(reflect)]`, and it can also be implemented by hand.
+///
+/// ```rust
+/// use binder::{AnyProperty, FieldInfo, Property, PropertyVisitor, Reflect};
+///
+/// struct Camera {
+///     fov: Property<f32>,
+///     name: Property<String>,
+/// }
+///
+/// impl Reflect for Camera {
+///     fn visit<'a>(&'a self, visitor: &mut dyn PropertyVisitor<'a>) {
+///         visitor.visit(&FieldInfo::new("fov", "", Some((1.0, 179.0))), &self.fov);
+///         visitor.visit(&FieldInfo::new("name", "", None), &self.name);
+///     }
+/// }
+///
+/// let camera = Camera { fov: Property::new(90.0), name: Property::new(String::from("main")) };
+/// let fov = camera.property("fov").unwrap();
+/// assert_eq!(fov.type_name(), "f32");
+/// *fov.bind_any().downcast_mut::<f32>().unwrap() = 60.0;
+/// assert_eq!(*camera.fov.bind_ref(), 60.0);
+/// ```
+pub trait Reflect {
+    /// Calls the visitor for every property.
+    fn visit<'a>(&'a self, visitor: &mut dyn PropertyVisitor<'a>);
+
+    /// Finds a property by name.
+    fn property(&self, name: &str) -> Option<&dyn AnyProperty> {
+        struct Find<'a, 'n> {
+            name: &'n str,
+            found: Option<&'a dyn AnyProperty>,
+        }
+
+        impl<'a, 'n> PropertyVisitor<'a> for Find<'a, 'n> {
+            fn visit(&mut self, info: &FieldInfo, property: &'a dyn AnyProperty) {
+                if self.found.is_none() && info.name() == self.name {
+                    self.found = Some(property);
+                }
+            }
+        }
+
+        let mut find = Find { name, found: None };
+        self.visit(&mut find);
+        find.found
+    }
+}
@@ -0,0 +1,26 @@
+//! `Serialize` and `Deserialize` for [Property], behind the `serde` feature.
+
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::ser::Error;
+
+use crate::Property;
+
+
+/// Serializes the value, which is bound for reading while it's being serialized. Fails if the
+/// property is bound mutably or poisoned, since the value can't be read then.
+impl<T: Serialize> Serialize for Property<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.try_bind_ref() {
+            Ok(value) => value.serialize(serializer),
+            Err(e) => Err(S::Error::custom(format_args!("can't serialize Property<{}>: {}", std::any::type_name::<T>(), e)))
+        }
+    }
+}
+
+/// Deserializes a value and wraps it in a new, unbound property with no hooks.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Property<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Property::new)
+    }
+}
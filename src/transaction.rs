@@ -0,0 +1,154 @@
+//! Changing several properties at once, all or nothing.
+
+
+use std::fmt;
+use std::ptr::NonNull;
+
+use crate::{BindError, Property, PropertyBinding, ValidationError};
+
+
+/// A property bound by a [Transaction], along with its value from before.
+struct Staged<'a, T> {
+    binding: PropertyBinding<'a, T>,
+    original: T,
+}
+
+/// Type-erased [Staged], so a transaction can hold properties of different types.
+trait Entry {
+    fn value(&mut self) -> NonNull<()>;
+    fn validate(&mut self) -> Result<(), ValidationError>;
+    fn notify(&mut self);
+    fn rollback(&mut self);
+}
+
+impl<'a, T: Clone> Entry for Staged<'a, T> {
+    fn value(&mut self) -> NonNull<()> {
+        NonNull::from(&mut *self.binding).cast()
+    }
+
+    fn validate(&mut self) -> Result<(), ValidationError> {
+        self.binding.validate()
+    }
+
+    fn notify(&mut self) {
+        self.binding.notify()
+    }
+
+    fn rollback(&mut self) {
+        self.binding.restore(self.original.clone());
+    }
+}
+
+/// A set of changes to any number of properties, which are either committed together or rolled
+/// back together, e.g. the fields of a dialog that has OK and Cancel buttons.
+///
+/// Each property is bound the first time it's changed through the transaction, and stays bound
+/// until the transaction ends. [commit](Transaction::commit) runs every property's validators
+/// first, and only if all of them accept the new values are observers notified, once per
+/// property. Otherwise, and if the transaction is [rollback](Transaction::rollback)ed or dropped
+/// without committing, every property gets its original value back and observers aren't
+/// notified at all. That includes a transaction that's dropped during a panic, so its properties
+/// aren't poisoned.
+///
+/// ```rust
+/// let name = binder::Property::new(String::from("untitled"));
+/// let size = binder::Property::new(10u32);
+/// let mut tx = binder::Transaction::new();
+/// tx.set(&name, String::from("level 1")).unwrap();
+/// tx.update(&size, |s| *s *= 2).unwrap();
+/// tx.rollback();
+/// assert_eq!((name.bind_ref().as_str(), *size.bind_ref()), ("untitled", 10));
+/// ```
+#[derive(Default)]
+pub struct Transaction<'a> {
+    /// Keyed by the address of each property's lock, which is unique to the property.
+    entries: Vec<(usize, Box<dyn Entry + Send + 'a>)>,
+}
+
+impl<'a> Transaction<'a> {
+    /// Creates an empty transaction.
+    pub fn new() -> Self {
+        Transaction { entries: Vec::new() }
+    }
+
+    /// Changes a property's value with a closure. The property is bound with
+    /// [try_bind](Property::try_bind) if this transaction hasn't bound it yet, and fails under
+    /// the same conditions. A failure leaves the rest of the transaction as it was.
+    #[track_caller]
+    pub fn update<T, F>(&mut self, property: &'a Property<T>, f: F) -> Result<(), BindError>
+        where T: Clone + Send + 'a, F: FnOnce(&mut T)
+    {
+        let key = &property.mut_lock as *const _ as usize;
+        let mut value = match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, entry)) => entry.value().cast::<T>(),
+            None => {
+                let binding = property.try_bind()?;
+                let original = binding.clone();
+                let mut entry: Box<dyn Entry + Send + 'a> = Box::new(Staged { binding, original });
+                let value = entry.value().cast::<T>();
+                self.entries.push((key, entry));
+                value
+            }
+        };
+        // SAFETY: the key identifies the property, which has the type `T`, and the entry holds a
+        // binding to it that's mutably dereferenced by `value()`
+        f(unsafe { value.as_mut() });
+        Ok(())
+    }
+
+    /// Sets a property's value. See [update](Transaction::update).
+    #[track_caller]
+    pub fn set<T>(&mut self, property: &'a Property<T>, value: T) -> Result<(), BindError>
+        where T: Clone + Send + 'a
+    {
+        self.update(property, move |v| *v = value)
+    }
+
+    /// Returns the number of properties bound by the transaction.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no property was changed through the transaction yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Validates every change and, if they're all accepted, notifies observers and unbinds the
+    /// properties. If any change is rejected, every property is rolled back instead, and the
+    /// first validation error is returned.
+    pub fn commit(mut self) -> Result<(), ValidationError> {
+        let result = self.entries.iter_mut().try_for_each(|(_, entry)| entry.validate());
+        match result {
+            Ok(()) => self.entries.iter_mut().for_each(|(_, entry)| entry.notify()),
+            Err(_) => self.rollback_all(),
+        }
+        self.entries.clear();
+        result
+    }
+
+    /// Restores every property's original value and unbinds them, without notifying observers.
+    /// Dropping the transaction does the same.
+    pub fn rollback(mut self) {
+        self.rollback_all();
+    }
+
+    fn rollback_all(&mut self) {
+        for (_, entry) in self.entries.iter_mut().rev() {
+            entry.rollback();
+        }
+        self.entries.clear();
+    }
+}
+
+impl<'a> Drop for Transaction<'a> {
+    fn drop(&mut self) {
+        self.rollback_all();
+    }
+}
+
+impl<'a> fmt::Debug for Transaction<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transaction").field("properties", &self.entries.len()).finish()
+    }
+}
@@ -0,0 +1,322 @@
+//! A registry of properties, organized by dotted paths.
+
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::ops::Bound;
+use std::str::FromStr;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{AnyProperty, ArcPropertyBinding, BindError, LoadError, LoadErrorKind, Property, TreeError};
+
+
+/// A registered property, type-erased in two ways: `Any` to get the typed `Arc` back, and
+/// `AnyProperty` to use it without knowing the type.
+struct Entry {
+    property: Arc<dyn Any + Send + Sync>,
+    erased: Arc<dyn AnyProperty + Send + Sync>,
+    persist: Option<Persist>,
+}
+
+/// Converts the value of a property registered with `register_persistent` to and from text.
+/// Both get the `Property<T>` as `Any`.
+struct Persist {
+    save: fn(&(dyn Any + Send + Sync)) -> Result<String, BindError>,
+    load: fn(&(dyn Any + Send + Sync), &str) -> Result<(), LoadErrorKind>,
+}
+
+fn save_value<T: Display + 'static>(property: &(dyn Any + Send + Sync)) -> Result<String, BindError> {
+    let property = property.downcast_ref::<Property<T>>().unwrap();
+    let value = property.try_bind_ref()?;
+    Ok(value.to_string())
+}
+
+fn load_value<T>(property: &(dyn Any + Send + Sync), text: &str) -> Result<(), LoadErrorKind>
+    where T: FromStr + 'static, T::Err: Display
+{
+    let property = property.downcast_ref::<Property<T>>().unwrap();
+    let value = text.parse::<T>().map_err(|e| LoadErrorKind::Parse(e.to_string()))?;
+    let mut binding = property.try_bind().map_err(LoadErrorKind::Bind)?;
+    *binding = value;
+    binding.commit().map_err(LoadErrorKind::Validation)
+}
+
+/// Quotes a value if it wouldn't be read back the same way otherwise.
+fn quote(value: &str) -> Cow<'_, str> {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(char::is_whitespace) || value.ends_with(char::is_whitespace)
+        || value.starts_with('"') || value.contains(['\n', '\r']);
+    if !needs_quotes {
+        return Cow::Borrowed(value);
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+/// Reads a value written by `quote`, which has already been trimmed.
+fn unquote(value: &str) -> Result<Cow<'_, str>, LoadErrorKind> {
+    let rest = match value.strip_prefix('"') {
+        Some(rest) => rest,
+        None => return Ok(Cow::Borrowed(value))
+    };
+    let mut unquoted = String::with_capacity(rest.len());
+    let mut chars = rest.chars();
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            Some('\\') => match chars.next() {
+                Some('"') => unquoted.push('"'),
+                Some('\\') => unquoted.push('\\'),
+                Some('n') => unquoted.push('\n'),
+                Some('r') => unquoted.push('\r'),
+                Some('t') => unquoted.push('\t'),
+                Some(c) => return Err(LoadErrorKind::Syntax(format!("unknown escape sequence '\\{}'", c))),
+                None => return Err(LoadErrorKind::Syntax(String::from("unterminated string")))
+            },
+            Some(c) => unquoted.push(c),
+            None => return Err(LoadErrorKind::Syntax(String::from("unterminated string")))
+        }
+    }
+    if !chars.as_str().is_empty() {
+        return Err(LoadErrorKind::Syntax(String::from("unexpected text after string")));
+    }
+    Ok(Cow::Owned(unquoted))
+}
+
+/// A registry of properties shared through [Arc](std::sync::Arc)s, each registered under a path
+/// like `render.shadows.quality`, so debug consoles, config loaders and the like can find them
+/// by name. Paths are made of segments of ASCII letters, digits, `_` and `-`, separated by dots.
+///
+/// Properties can be looked up with their type through [get](PropertyTree::get), which fails
+/// with [TreeError::TypeMismatch] if the type is wrong, or without it through
+/// [get_any](PropertyTree::get_any). The tree keeps registered properties alive until they're
+/// unregistered.
+///
+/// Properties registered with [register_persistent](PropertyTree::register_persistent) can also
+/// be saved as text and loaded again, see [save](PropertyTree::save).
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// let tree = binder::PropertyTree::new();
+/// let quality = Arc::new(binder::Property::new(2u32));
+/// tree.register("render.shadows.quality", &quality).unwrap();
+/// tree.register("render.shadows.enabled", &Arc::new(binder::Property::new(true))).unwrap();
+///
+/// *tree.try_bind::<u32>("render.shadows.quality").unwrap() = 3;
+/// assert_eq!(*quality.bind_ref(), 3);
+/// assert!(tree.get::<f32>("render.shadows.quality").is_err());
+/// assert_eq!(tree.paths("render.shadows"), ["render.shadows.enabled", "render.shadows.quality"]);
+/// ```
+#[derive(Default)]
+pub struct PropertyTree {
+    entries: RwLock<BTreeMap<String, Entry>>,
+}
+
+/// Returns `true` if `path` is a valid path, see [PropertyTree].
+fn is_valid_path(path: &str) -> bool {
+    path.split('.').all(|segment| {
+        !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+    })
+}
+
+/// Returns `true` if `path` is `prefix` or one of its descendants.
+fn has_prefix(path: &str, prefix: &str) -> bool {
+    prefix.is_empty() || (path.starts_with(prefix) && matches!(path.as_bytes().get(prefix.len()), None | Some(b'.')))
+}
+
+impl PropertyTree {
+    /// Creates an empty tree.
+    pub fn new() -> Self {
+        PropertyTree { entries: RwLock::new(BTreeMap::new()) }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, BTreeMap<String, Entry>> {
+        // the map is never left half-updated, so poisoning doesn't matter
+        self.entries.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, BTreeMap<String, Entry>> {
+        self.entries.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Registers a property under a path. Fails with [TreeError::InvalidPath] if the path isn't
+    /// valid, and with [TreeError::AlreadyRegistered] if another property is registered under it.
+    pub fn register<T>(&self, path: impl Into<String>, property: &Arc<Property<T>>) -> Result<(), TreeError>
+        where T: Send + Sync + 'static
+    {
+        self.insert(path.into(), property, None)
+    }
+
+    /// Registers a property like [register](PropertyTree::register), and includes it when the
+    /// tree is [save](PropertyTree::save)d or [load](PropertyTree::load)ed. Its value is written
+    /// with [Display] and read back with [FromStr], so they need to round-trip.
+    pub fn register_persistent<T>(&self, path: impl Into<String>, property: &Arc<Property<T>>) -> Result<(), TreeError>
+        where T: Display + FromStr + Send + Sync + 'static, T::Err: Display
+    {
+        self.insert(path.into(), property, Some(Persist { save: save_value::<T>, load: load_value::<T> }))
+    }
+
+    fn insert<T>(&self, path: String, property: &Arc<Property<T>>, persist: Option<Persist>) -> Result<(), TreeError>
+        where T: Send + Sync + 'static
+    {
+        if !is_valid_path(&path) {
+            return Err(TreeError::InvalidPath(path));
+        }
+        let mut entries = self.write();
+        if entries.contains_key(&path) {
+            return Err(TreeError::AlreadyRegistered(path));
+        }
+        entries.insert(path, Entry { property: property.clone(), erased: property.clone(), persist });
+        Ok(())
+    }
+
+    /// Removes the property registered under a path, returning `true` if there was one.
+    pub fn unregister(&self, path: &str) -> bool {
+        self.write().remove(path).is_some()
+    }
+
+    /// Returns `true` if a property is registered under the path.
+    pub fn contains(&self, path: &str) -> bool {
+        self.read().contains_key(path)
+    }
+
+    /// Looks up a property with a known value type.
+    pub fn get<T: Send + Sync + 'static>(&self, path: &str) -> Result<Arc<Property<T>>, TreeError> {
+        let entries = self.read();
+        let entry = entries.get(path).ok_or_else(|| TreeError::NotFound(path.to_string()))?;
+        entry.property.clone().downcast::<Property<T>>().map_err(|_| TreeError::TypeMismatch {
+            path: path.to_string(),
+            expected: std::any::type_name::<T>(),
+            found: entry.erased.type_name(),
+        })
+    }
+
+    /// Looks up a property without knowing its value type.
+    pub fn get_any(&self, path: &str) -> Result<Arc<dyn AnyProperty + Send + Sync>, TreeError> {
+        self.read().get(path).map(|entry| entry.erased.clone()).ok_or_else(|| TreeError::NotFound(path.to_string()))
+    }
+
+    /// Looks up a property and binds it with [try_bind_arc](Property::try_bind_arc).
+    #[track_caller]
+    pub fn try_bind<T: Send + Sync + 'static>(&self, path: &str) -> Result<ArcPropertyBinding<T>, TreeError> {
+        Ok(self.get::<T>(path)?.try_bind_arc()?)
+    }
+
+    /// Returns the paths of every property registered under `prefix`, including `prefix` itself,
+    /// in sorted order. Only whole segments match, so `render` matches `render.scale` but not
+    /// `renderer.scale`. An empty prefix matches every path.
+    pub fn paths(&self, prefix: &str) -> Vec<String> {
+        self.read().range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(path, _)| path)
+            .take_while(|path| path.starts_with(prefix))
+            .filter(|path| has_prefix(path, prefix))
+            .cloned()
+            .collect()
+    }
+
+    /// Writes the value of every property registered with
+    /// [register_persistent](PropertyTree::register_persistent) as text, one `path = value` line
+    /// each, sorted by path. Values are written with [Display], and put in double quotes with
+    /// `\\`, `\"`, `\n`, `\r` and `\t` escapes if they'd be ambiguous otherwise, e.g. if they're
+    /// empty or span several lines.
+    ///
+    /// Fails if one of the properties is bound mutably, see [try_bind_ref](Property::try_bind_ref).
+    ///
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// let tree = binder::PropertyTree::new();
+    /// let scale = Arc::new(binder::Property::new(1.5f32));
+    /// tree.register_persistent("ui.scale", &scale).unwrap();
+    /// tree.register_persistent("ui.title", &Arc::new(binder::Property::new(String::from(" Hi ")))).unwrap();
+    /// let text = tree.save().unwrap();
+    /// assert_eq!(text, "ui.scale = 1.5\nui.title = \" Hi \"\n");
+    ///
+    /// *scale.bind() = 3.0;
+    /// tree.load(&text).unwrap();
+    /// assert_eq!(*scale.bind_ref(), 1.5);
+    /// ```
+    pub fn save(&self) -> Result<String, TreeError> {
+        let mut text = String::new();
+        for (path, entry) in self.read().iter() {
+            if let Some(persist) = &entry.persist {
+                let value = (persist.save)(&*entry.property)?;
+                text.push_str(&format!("{} = {}\n", path, quote(&value)));
+            }
+        }
+        Ok(text)
+    }
+
+    /// Reads text written by [save](PropertyTree::save), and sets every property in it. Blank
+    /// lines and lines starting with `#` are skipped, and values can also be left unquoted.
+    ///
+    /// Each value is set by binding the property with [try_bind](Property::try_bind) and
+    /// [commit](crate::PropertyBinding::commit)ting the change, so validators and observers run
+    /// as usual. The tree isn't locked while that happens, so they can use it too. A line that
+    /// can't be applied doesn't stop the rest from being loaded: every problem is collected and
+    /// returned at the end, along with its line number.
+    pub fn load(&self, text: &str) -> Result<(), Vec<LoadError>> {
+        let mut errors = Vec::new();
+        // look the properties up first, and only set them once the tree is unlocked
+        let mut found = Vec::new();
+        {
+            let entries = self.read();
+            for (i, line) in text.lines().enumerate() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let error = |path: Option<&str>, kind| LoadError { line: i + 1, path: path.map(String::from), kind };
+                let (path, value) = match line.split_once('=') {
+                    Some((path, value)) => (path.trim(), value.trim()),
+                    None => {
+                        errors.push(error(None, LoadErrorKind::Syntax(String::from("expected `path = value`"))));
+                        continue;
+                    }
+                };
+                match entries.get(path) {
+                    Some(Entry { property, persist: Some(persist), .. }) => {
+                        found.push((i, path, value, property.clone(), persist.load));
+                    }
+                    _ => errors.push(error(Some(path), LoadErrorKind::UnknownKey))
+                }
+            }
+        }
+        for (i, path, value, property, load) in found {
+            if let Err(kind) = unquote(value).and_then(|value| load(&*property, value.as_ref())) {
+                errors.push(LoadError { line: i + 1, path: Some(String::from(path)), kind });
+            }
+        }
+        errors.sort_by_key(|e| e.line);
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Returns the number of registered properties.
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Returns `true` if no properties are registered.
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+}
+
+impl fmt::Debug for PropertyTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.read().iter().map(|(path, entry)| (path, entry.erased.type_name()))).finish()
+    }
+}
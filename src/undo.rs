@@ -0,0 +1,330 @@
+//! Undo/redo history for properties.
+
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem::size_of;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::thread::{self, ThreadId};
+
+use crate::{Property, Subscription, UndoError};
+
+
+/// One recorded change to one property. `apply(true)` restores the old value, `apply(false)` the
+/// new one.
+struct Change {
+    size: usize,
+    apply: Box<dyn Fn(bool) -> Result<(), UndoError> + Send>,
+}
+
+/// A single undo step, made of one or more changes.
+struct Entry {
+    name: Option<String>,
+    changes: Vec<Change>,
+}
+
+impl Entry {
+    fn size(&self) -> usize {
+        self.changes.iter().map(|c| c.size).sum()
+    }
+
+    /// Applies every change in the right order. If one of them fails, the ones already applied
+    /// are reverted again, so the entry is never left half-applied.
+    fn apply(&self, undo: bool) -> Result<(), UndoError> {
+        let order: Vec<&Change> = if undo { self.changes.iter().rev().collect() } else { self.changes.iter().collect() };
+        for (i, change) in order.iter().enumerate() {
+            if let Err(e) = (change.apply)(undo) {
+                for change in order[..i].iter().rev() {
+                    let _ = (change.apply)(!undo);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+struct History {
+    undo: VecDeque<Entry>,
+    redo: Vec<Entry>,
+    /// The groups being recorded, by the thread that started them, and how many [UndoGroup]s
+    /// are open on that thread.
+    groups: Vec<(ThreadId, Entry, usize)>,
+    /// Changes committed by this thread are being replayed, so they mustn't be recorded.
+    replaying: Option<ThreadId>,
+    memory: usize,
+    max_depth: usize,
+    max_memory: usize,
+}
+
+impl History {
+    fn push(&mut self, entry: Entry) {
+        if entry.changes.is_empty() {
+            return;
+        }
+        self.memory += entry.size();
+        self.undo.push_back(entry);
+        self.trim();
+    }
+
+    /// Forgets the oldest entries until the history is within its limits again.
+    fn trim(&mut self) {
+        while self.undo.len() > self.max_depth || (self.memory > self.max_memory && !self.undo.is_empty()) {
+            let entry = self.undo.pop_front().unwrap();
+            self.memory -= entry.size();
+        }
+    }
+
+    fn record(&mut self, change: Change) {
+        let thread = thread::current().id();
+        if self.replaying == Some(thread) {
+            return;
+        }
+        self.redo.clear();
+        match self.groups.iter_mut().find(|(t, _, _)| *t == thread) {
+            Some((_, entry, _)) => entry.changes.push(change),
+            None => self.push(Entry { name: None, changes: vec![change] }),
+        }
+    }
+}
+
+fn lock(history: &Mutex<History>) -> MutexGuard<'_, History> {
+    // the lock is never held while user code runs, so poisoning can't leave the history half-updated
+    history.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Undo/redo history for any number of properties. Properties opt in with
+/// [track](UndoStack::track), after which every [PropertyBinding](crate::PropertyBinding) that
+/// commits a change to them records an undo entry with the old and new value. Several changes
+/// can be recorded as a single, named entry with [group](UndoStack::group).
+///
+/// [undo](UndoStack::undo) and [redo](UndoStack::redo) restore values by binding the properties
+/// again, so validators and observers run just like they would for any other change. Those
+/// changes aren't recorded themselves. If a validator rejects one of them, the entry is left
+/// where it was, just like when a property can't be bound.
+///
+/// By default the history is unlimited. [with_max_depth](UndoStack::with_max_depth) and
+/// [with_max_memory](UndoStack::with_max_memory) make it forget the oldest entries instead.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// let undo = binder::UndoStack::new();
+/// let x = Arc::new(binder::Property::new(0.0f32));
+/// let y = Arc::new(binder::Property::new(0.0f32));
+/// let _tracking = [undo.track(&x), undo.track(&y)];
+///
+/// *x.bind() = 1.0;
+/// {
+///     let _group = undo.group("Move");
+///     *x.bind() = 2.0;
+///     *y.bind() = 3.0;
+/// }
+/// assert_eq!(undo.undo_name().as_deref(), Some("Move"));
+/// undo.undo().unwrap();
+/// assert_eq!((*x.bind_ref(), *y.bind_ref()), (1.0, 0.0));
+/// undo.redo().unwrap();
+/// assert_eq!((*x.bind_ref(), *y.bind_ref()), (2.0, 3.0));
+/// ```
+pub struct UndoStack {
+    history: Arc<Mutex<History>>,
+}
+
+impl UndoStack {
+    /// Creates an empty, unlimited history.
+    pub fn new() -> Self {
+        UndoStack {
+            history: Arc::new(Mutex::new(History {
+                undo: VecDeque::new(),
+                redo: Vec::new(),
+                groups: Vec::new(),
+                replaying: None,
+                memory: 0,
+                max_depth: usize::MAX,
+                max_memory: usize::MAX,
+            }))
+        }
+    }
+
+    /// Limits the number of entries that can be undone.
+    pub fn with_max_depth(self, depth: usize) -> Self {
+        lock(&self.history).max_depth = depth;
+        self
+    }
+
+    /// Limits the memory used by entries that can be undone, in bytes. This is an estimate based
+    /// on [size_of](std::mem::size_of) the recorded values, so anything they own on the heap
+    /// isn't counted.
+    pub fn with_max_memory(self, bytes: usize) -> Self {
+        lock(&self.history).max_memory = bytes;
+        self
+    }
+
+    /// Starts recording every change to `property` in this history, until the returned
+    /// [Subscription] is dropped. Entries that were already recorded can still be undone
+    /// afterwards.
+    pub fn track<T>(&self, property: &Arc<Property<T>>) -> Subscription
+        where T: Clone + Send + Sync + 'static
+    {
+        let history = Arc::downgrade(&self.history);
+        let weak = Arc::downgrade(property);
+        property.subscribe(move |old, new| {
+            if let Some(history) = history.upgrade() {
+                lock(&history).record(change(weak.clone(), old.clone(), new.clone()));
+            }
+        })
+    }
+
+    /// Starts a group. Every change committed on the calling thread until the returned
+    /// [UndoGroup] is dropped is undone and redone together as a single entry. Changes committed
+    /// on other threads in the meantime are recorded as usual, or in their own thread's group.
+    /// Groups can be nested, in which case everything is recorded in the outermost one, under
+    /// its name.
+    pub fn group(&self, name: impl Into<String>) -> UndoGroup<'_> {
+        let thread = thread::current().id();
+        let mut history = lock(&self.history);
+        match history.groups.iter_mut().find(|(t, _, _)| *t == thread) {
+            Some((_, _, depth)) => *depth += 1,
+            None => history.groups.push((thread, Entry { name: Some(name.into()), changes: Vec::new() }, 1)),
+        }
+        UndoGroup { stack: self, thread }
+    }
+
+    /// Undoes the most recent entry. Returns `Ok(false)` if there was nothing to undo.
+    ///
+    /// Returns an error if any of the entry's properties couldn't be bound (see
+    /// [try_bind](Property::try_bind)), or rejected its old value, in which case nothing is
+    /// undone and the entry stays where it was. Properties that were dropped in the meantime are
+    /// skipped.
+    pub fn undo(&self) -> Result<bool, UndoError> {
+        self.replay(true)
+    }
+
+    /// Redoes the most recently undone entry. Returns `Ok(false)` if there was nothing to redo.
+    /// Fails under the same conditions as [undo](UndoStack::undo).
+    pub fn redo(&self) -> Result<bool, UndoError> {
+        self.replay(false)
+    }
+
+    fn replay(&self, undo: bool) -> Result<bool, UndoError> {
+        let entry = {
+            let mut history = lock(&self.history);
+            let entry = if undo { history.undo.pop_back() } else { history.redo.pop() };
+            match entry {
+                Some(entry) => {
+                    if undo {
+                        history.memory -= entry.size();
+                    }
+                    history.replaying = Some(thread::current().id());
+                    entry
+                }
+                None => return Ok(false)
+            }
+        };
+        // the history isn't locked here, since observers of the properties record changes in it
+        let result = entry.apply(undo);
+        let mut history = lock(&self.history);
+        history.replaying = None;
+        match (undo, result.is_ok()) {
+            (true, true) | (false, false) => history.redo.push(entry),
+            (true, false) | (false, true) => {
+                history.memory += entry.size();
+                history.undo.push_back(entry);
+                history.trim();
+            }
+        }
+        result.map(|()| true)
+    }
+
+    /// Returns `true` if there's an entry to undo.
+    pub fn can_undo(&self) -> bool {
+        !lock(&self.history).undo.is_empty()
+    }
+
+    /// Returns `true` if there's an entry to redo.
+    pub fn can_redo(&self) -> bool {
+        !lock(&self.history).redo.is_empty()
+    }
+
+    /// The name of the entry [undo](UndoStack::undo) would undo, if it's a named group.
+    pub fn undo_name(&self) -> Option<String> {
+        lock(&self.history).undo.back().and_then(|e| e.name.clone())
+    }
+
+    /// The name of the entry [redo](UndoStack::redo) would redo, if it's a named group.
+    pub fn redo_name(&self) -> Option<String> {
+        lock(&self.history).redo.last().and_then(|e| e.name.clone())
+    }
+
+    /// Forgets every entry.
+    pub fn clear(&self) {
+        let mut history = lock(&self.history);
+        history.undo.clear();
+        history.redo.clear();
+        history.memory = 0;
+    }
+}
+
+/// Records a change to a property, which is replayed with [try_bind](Property::try_bind) and
+/// [commit](crate::PropertyBinding::commit).
+fn change<T>(property: Weak<Property<T>>, old: T, new: T) -> Change
+    where T: Clone + Send + Sync + 'static
+{
+    Change {
+        size: size_of::<Change>() + 2 * size_of::<T>(),
+        apply: Box::new(move |undo| {
+            if let Some(property) = property.upgrade() {
+                let mut binding = property.try_bind()?;
+                *binding = if undo { old.clone() } else { new.clone() };
+                binding.commit()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for UndoStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let history = lock(&self.history);
+        f.debug_struct("UndoStack")
+            .field("undo", &history.undo.len())
+            .field("redo", &history.redo.len())
+            .field("memory", &history.memory)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Handle to a group started by [UndoStack::group]. The group ends when it's
+/// [Drop](std::ops::Drop)ped.
+#[must_use = "the group ends as soon as the UndoGroup is dropped"]
+pub struct UndoGroup<'a> {
+    stack: &'a UndoStack,
+    /// The thread whose changes are grouped, in case this is dropped on another one.
+    thread: ThreadId,
+}
+
+impl<'a> Drop for UndoGroup<'a> {
+    fn drop(&mut self) {
+        let mut history = lock(&self.stack.history);
+        if let Some(i) = history.groups.iter().position(|(t, _, _)| *t == self.thread) {
+            if history.groups[i].2 > 1 {
+                history.groups[i].2 -= 1;
+            }
+            else {
+                let (_, entry, _) = history.groups.remove(i);
+                history.push(entry);
+            }
+        }
+    }
+}
+
+impl<'a> fmt::Debug for UndoGroup<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UndoGroup").finish_non_exhaustive()
+    }
+}
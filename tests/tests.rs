@@ -1,4 +1,5 @@
 #![allow(dead_code)]
+#![allow(clippy::bool_assert_comparison, clippy::explicit_auto_deref)]
 
 use binder::Property;
 
@@ -116,4 +117,1431 @@ fn double_bind_result() {
 /// let _ = &mut bind;
 /// let _ = &mut bind;
 /// ```
-struct _Doctest;
\ No newline at end of file
+struct _Doctest;
+
+/// ```compile_fail
+/// let p = binder::Property::new(1f32);
+/// let bind = p.bind();
+/// drop(p);
+/// let _ = *bind;
+/// ```
+struct _DoctestDropWhileBound;
+
+/// ```compile_fail
+/// let p = binder::Property::new(1f32);
+/// let bind = p.bind();
+/// let moved = p;
+/// let _ = *bind;
+/// ```
+struct _DoctestMoveWhileBound;
+
+/// ```compile_fail
+/// let bind = {
+///     let p = binder::Property::new(1f32);
+///     p.bind()
+/// };
+/// let _ = *bind;
+/// ```
+struct _DoctestOutlivesProperty;
+
+/// ```compile_fail
+/// fn dangling() -> binder::PropertyBinding<'static, f32> {
+///     binder::Property::new(1f32).bind()
+/// }
+/// ```
+struct _DoctestStaticBinding;
+
+#[test]
+fn arc_binding() {
+    use std::sync::Arc;
+    struct Holder { binding: binder::ArcPropertyBinding<i32> }
+
+    let p = Arc::new(Property::new(1i32));
+    let mut holder = Holder { binding: p.bind_arc() };
+    *holder.binding += 1;
+    assert!(p.try_bind().is_err());
+    assert!(p.try_bind_arc().is_err());
+    drop(holder);
+    assert_eq!(*p.bind(), 2);
+}
+
+#[test]
+fn arc_binding_outlives_property() {
+    use std::sync::Arc;
+    let mut binding = Arc::new(Property::new(String::from("test"))).bind_arc();
+    binding.push_str(" test");
+    assert_eq!(binding.as_str(), "test test");
+}
+
+#[test]
+fn arc_binding_send() {
+    use std::sync::Arc;
+    let p = Arc::new(Property::new(1i32));
+    let mut binding = p.bind_arc();
+    std::thread::spawn(move || {
+        *binding = 5;
+    }).join().unwrap();
+    assert_eq!(*p.bind(), 5);
+}
+
+#[test]
+fn observers() {
+    use std::sync::{Arc, Mutex};
+    let p = Property::new(1i32);
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let sub = {
+        let changes = changes.clone();
+        p.subscribe(move |old, new| changes.lock().unwrap().push((*old, *new)))
+    };
+
+    *p.bind() = 2;
+    let _ = *p.bind();
+    {
+        let mut b = p.bind();
+        *b += 1;
+        *b += 1;
+    }
+    assert_eq!(*changes.lock().unwrap(), vec![(1, 2), (2, 4)]);
+
+    drop(sub);
+    *p.bind() = 5;
+    assert_eq!(changes.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn subscription_outlives_property() {
+    let p = Property::new(String::from("test"));
+    let sub = p.subscribe(|_, _| {});
+    drop(p);
+    drop(sub);
+}
+
+#[test]
+fn shared_read_bindings() {
+    let p = Property::new(String::from("test"));
+    let r1 = p.bind_ref();
+    let r2 = p.bind_ref();
+    assert_eq!(r1.as_str(), "test");
+    assert_eq!(*r1, *r2);
+    assert!(p.try_bind().is_err());
+    drop(r1);
+    assert!(p.try_bind().is_err());
+    drop(r2);
+    let b = p.bind();
+    assert!(p.try_bind_ref().is_err());
+    drop(b);
+    assert!(p.try_bind_ref().is_ok());
+}
+
+#[test]
+#[should_panic(expected = "PropertyReadBinding<i32>: Tried to bind a property that was already bound mutably!")]
+fn bind_ref_while_bound_panic() {
+    let p = Property::new(1i32);
+    let _bind = p.bind();
+    p.bind_ref();
+}
+
+#[test]
+fn read_bindings_across_threads() {
+    let p = Property::new(vec![1, 2, 3]);
+    let r = p.bind_ref();
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| assert_eq!(p.bind_ref().iter().sum::<i32>(), 6));
+        }
+    });
+    assert_eq!(r.len(), 3);
+}
+
+/// ```compile_fail
+/// let p = binder::Property::new(1f32);
+/// let mut bind = p.bind_ref();
+/// *bind = 2.0;
+/// ```
+struct _DoctestReadBindingIsReadOnly;
+
+#[test]
+fn bind_errors() {
+    use binder::BindError;
+    let p = Property::new(1i32);
+    {
+        let _bind = p.bind();
+        assert!(matches!(p.try_bind().unwrap_err(), BindError::WouldDeadlock(_)));
+        assert!(matches!(p.try_bind_ref().unwrap_err(), BindError::WouldDeadlock(_)));
+        std::thread::scope(|s| {
+            s.spawn(|| assert!(matches!(p.try_bind().unwrap_err(), BindError::AlreadyBound(_))));
+        });
+    }
+    {
+        let _bind = p.bind_ref();
+        assert!(matches!(p.try_bind().unwrap_err(), BindError::AlreadyBound(_)));
+    }
+    let err: Box<dyn std::error::Error> = Box::new(BindError::AlreadyBound(None));
+    assert_eq!(err.to_string(), "property is already bound");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn holder_diagnostics() {
+    let p = Property::new(1i32);
+    let line = line!() + 1;
+    let bind = p.bind();
+    let err = p.try_bind().unwrap_err();
+    let holder = err.holder().unwrap();
+    assert_eq!(holder.location().file(), file!());
+    assert_eq!(holder.location().line(), line);
+    assert_eq!(holder.thread_id(), std::thread::current().id());
+    assert!(holder.is_mutable());
+    assert!(err.to_string().contains(&format!("{}:{}", file!(), line)));
+    drop(bind);
+
+    let _r1 = p.bind_ref();
+    let _r2 = p.bind_ref();
+    let err = std::thread::scope(|s| {
+        std::thread::Builder::new().name(String::from("other"))
+            .spawn_scoped(s, || p.try_bind().unwrap_err())
+            .unwrap().join().unwrap()
+    });
+    let holder = err.holder().unwrap();
+    assert!(!holder.is_mutable());
+    assert_eq!(holder.thread_id(), std::thread::current().id());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "tests/tests.rs")]
+fn double_bind_panic_location() {
+    let p = Property::new(1i32);
+    let _bind = p.bind();
+    p.bind();
+}
+
+#[test]
+fn poisoning() {
+    use binder::BindError;
+    let p = Property::new(vec![1, 2, 3]);
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut b = p.bind();
+        b.push(4);
+        panic!("oops");
+    }));
+    assert!(p.is_poisoned());
+    assert_eq!(p.try_bind().unwrap_err(), BindError::Poisoned);
+    assert_eq!(p.try_bind_ref().unwrap_err(), BindError::Poisoned);
+
+    p.clear_poison();
+    assert!(!p.is_poisoned());
+    assert_eq!(*p.bind(), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn panicking_hooks_poison() {
+    use binder::BindError;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    let p = Property::new(1i32);
+    let _sub = p.subscribe(|_, new| if *new == 2 { panic!("observer") });
+    assert!(catch_unwind(AssertUnwindSafe(|| *p.bind() = 2)).is_err());
+    // unbound, but poisoned, rather than stuck
+    assert!(p.is_poisoned());
+    assert_eq!(p.try_bind().unwrap_err(), BindError::Poisoned);
+    p.clear_poison();
+    assert_eq!(p.try_get(), Ok(2));
+
+    let p = Property::new(1i32).with_validator(|v| if *v < 0 { panic!("validator") } else { Ok(()) });
+    assert!(catch_unwind(AssertUnwindSafe(|| p.set(-1))).is_err());
+    assert_eq!(p.try_bind_ref().unwrap_err(), BindError::Poisoned);
+    p.clear_poison();
+    assert!(p.bind_timeout(std::time::Duration::from_secs(5)).is_ok());
+}
+
+#[test]
+fn read_binding_does_not_poison() {
+    let p = Property::new(1i32);
+    let _ = std::panic::catch_unwind(|| {
+        let _r = p.bind_ref();
+        panic!("oops");
+    });
+    assert!(!p.is_poisoned());
+}
+
+#[test]
+fn into_inner_poisoned() {
+    let p = Property::new(String::from("test"));
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _b = p.bind();
+        panic!("oops");
+    }));
+    assert_eq!(p.into_inner(), "test");
+}
+
+#[test]
+#[should_panic(expected = "PropertyBinding<i32>: Tried to bind a poisoned property!")]
+fn poisoned_bind_panic() {
+    let p = Property::new(1i32);
+    let _ = std::panic::catch_unwind(|| {
+        let _b = p.bind();
+        panic!("oops");
+    });
+    p.bind();
+}
+
+#[test]
+fn blocking_bind() {
+    use std::time::Duration;
+    let p = Property::new(0i32);
+    let bind = p.bind();
+    std::thread::scope(|s| {
+        let waiter = s.spawn(|| *p.bind_blocking().unwrap() += 1);
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+        drop(bind);
+    });
+    assert_eq!(*p.bind(), 1);
+}
+
+#[test]
+fn blocking_bind_fifo() {
+    use std::future::Future;
+    use std::task::{Context, Poll, Waker};
+    let p = Property::new(Vec::new());
+    let bind = p.bind();
+    // futures get in line as soon as they're first polled, so the order is known up front
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut futures: Vec<_> = (0..5).map(|_| Box::pin(p.bind_async())).collect();
+    for future in &mut futures {
+        assert!(future.as_mut().poll(&mut cx).is_pending());
+    }
+    std::thread::scope(|s| {
+        // behind all of them, whenever it gets in line
+        s.spawn(|| p.bind_blocking().unwrap().push(5));
+        drop(bind);
+        // handed straight to the first in line, so nobody can cut in
+        assert!(p.try_bind().is_err());
+        assert!(std::thread::scope(|s| s.spawn(|| p.try_bind().is_err()).join().unwrap()));
+        for (i, future) in futures.iter_mut().enumerate() {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(mut b) => b.push(i),
+                Poll::Pending => panic!("future {} wasn't handed the lock", i)
+            }
+        }
+    });
+    assert_eq!(*p.bind(), vec![0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn blocking_bind_readers() {
+    use std::future::Future;
+    use std::task::{Context, Waker};
+    let p = Property::new(0i32);
+    let first = p.bind_ref();
+    let mut cx = Context::from_waker(Waker::noop());
+    let mut writer = Box::pin(p.bind_async());
+    assert!(writer.as_mut().poll(&mut cx).is_pending());
+    // a queued writer doesn't keep readers out, it waits for all of them
+    let second = std::thread::scope(|s| s.spawn(|| p.try_bind_ref().map(|r| *r)).join().unwrap());
+    assert_eq!(second, Ok(0));
+    let third = p.bind_ref();
+    drop(first);
+    assert!(writer.as_mut().poll(&mut cx).is_pending());
+    drop(third);
+    match writer.as_mut().poll(&mut cx) {
+        std::task::Poll::Ready(mut b) => *b = 1,
+        std::task::Poll::Pending => panic!("the last reader didn't hand off the lock")
+    }
+    drop(writer);
+    assert_eq!(*p.bind_ref(), 1);
+
+    // same for threads waiting in `bind_blocking`
+    let first = p.bind_ref();
+    std::thread::scope(|s| {
+        let writer = s.spawn(|| *p.bind_blocking().unwrap() = 2);
+        assert_eq!(s.spawn(|| p.try_bind_ref().map(|r| *r)).join().unwrap(), Ok(1));
+        assert!(!writer.is_finished());
+        drop(first);
+    });
+    assert_eq!(*p.bind_ref(), 2);
+}
+
+#[test]
+fn bind_timeout() {
+    use std::time::Duration;
+    use binder::BindError;
+    let p = Property::new(0i32);
+    let bind = p.bind();
+    std::thread::scope(|s| {
+        let res = s.spawn(|| p.bind_timeout(Duration::from_millis(20)).map(|_| ())).join().unwrap();
+        assert_eq!(res, Err(BindError::TimedOut));
+        let waiter = s.spawn(|| p.bind_timeout(Duration::from_secs(10)).map(|_| ()));
+        std::thread::sleep(Duration::from_millis(20));
+        drop(bind);
+        assert_eq!(waiter.join().unwrap(), Ok(()));
+    });
+    // timed out waiters leave the lock usable
+    assert!(p.try_bind_ref().is_ok());
+}
+
+#[test]
+fn blocking_bind_errors() {
+    use binder::BindError;
+    let p = Property::new(0i32);
+    {
+        let _bind = p.bind();
+        assert!(matches!(p.try_bind().map(|_| ()), Err(BindError::WouldDeadlock(_))));
+        assert_eq!(p.bind_timeout(std::time::Duration::from_millis(10)).map(|_| ()), Err(BindError::TimedOut));
+    }
+    // a binding sent to another thread is waited for, even though this thread created it. The
+    // sleeps only make it likely that the other thread is waiting, the results are the same
+    // either way.
+    let bind = p.bind();
+    std::thread::scope(|s| {
+        s.spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            drop(bind);
+        });
+        assert!(p.bind_blocking().is_ok());
+    });
+    let bind = p.bind();
+    std::thread::scope(|s| {
+        let waiter = s.spawn(|| p.bind_blocking().map(|_| ()));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+            let _bind = bind;
+            panic!("oops");
+        }));
+        assert_eq!(waiter.join().unwrap(), Err(BindError::Poisoned));
+    });
+}
+
+#[test]
+fn blocking_bind_contention() {
+    let p = Property::new(0usize);
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| for _ in 0..1000 {
+                *p.bind_blocking().unwrap() += 1;
+                let _ = p.try_bind_ref();
+            });
+        }
+    });
+    assert_eq!(*p.bind(), 8000);
+}
+
+/// Minimal executor for the async tests.
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    struct ThreadWaker(std::thread::Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) { self.0.unpark(); }
+    }
+    let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park()
+        }
+    }
+}
+
+#[test]
+fn async_bind() {
+    let p = Property::new(0i32);
+    *block_on(p.bind_async()) += 1;
+
+    let bind = p.bind();
+    std::thread::scope(|s| {
+        let waiter = s.spawn(|| block_on(async { *p.bind_async().await += 1 }));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        assert!(!waiter.is_finished());
+        drop(bind);
+    });
+    assert_eq!(*p.bind(), 2);
+}
+
+#[test]
+fn async_bind_cancelled() {
+    use std::future::Future;
+    use std::task::{Context, Waker};
+    let p = Property::new(0i32);
+    let bind = p.bind();
+    let mut cx = Context::from_waker(Waker::noop());
+
+    let mut queued = Box::pin(p.bind_async());
+    assert!(queued.as_mut().poll(&mut cx).is_pending());
+    drop(queued);
+    drop(bind);
+    assert!(p.try_bind().is_ok());
+
+    let bind = p.bind();
+    let mut granted = Box::pin(p.bind_async());
+    assert!(granted.as_mut().poll(&mut cx).is_pending());
+    drop(bind);
+    // the lock was handed to the future, dropping it has to pass the lock on
+    drop(granted);
+    assert!(p.try_bind().is_ok());
+}
+
+#[test]
+fn bind_all_tuple() {
+    let a = Property::new(1i32);
+    let b = Property::new(String::from("b"));
+    let c = Property::new(3.0f32);
+    {
+        let (mut a, mut b, c) = binder::bind_all((&a, &b, &c)).unwrap();
+        *a += 1;
+        b.push('!');
+        assert_eq!(*c, 3.0);
+    }
+    assert_eq!(*a.bind(), 2);
+    assert_eq!(b.bind().as_str(), "b!");
+    assert!(c.try_bind().is_ok());
+}
+
+#[test]
+fn try_bind_all_is_all_or_nothing() {
+    use binder::BindError;
+    let a = Property::new(1i32);
+    let b = Property::new(2i32);
+    let c = Property::new(3i32);
+    let _b = b.bind_ref();
+    assert!(binder::try_bind_all((&a, &b, &c)).is_err());
+    assert!(a.try_bind().is_ok());
+    assert!(c.try_bind().is_ok());
+    assert!(matches!(binder::try_bind_all((&a, &a)).map(|_| ()), Err(BindError::WouldDeadlock(_))));
+    assert!(matches!(binder::bind_all((&c, &a, &c)).map(|_| ()), Err(BindError::WouldDeadlock(_))));
+}
+
+#[test]
+fn bind_all_opposite_orders() {
+    let min = Property::new(0i64);
+    let max = Property::new(0i64);
+    std::thread::scope(|s| {
+        for t in 0..4 {
+            let (min, max) = (&min, &max);
+            s.spawn(move || for _ in 0..500 {
+                let (mut lo, mut hi) = if t % 2 == 0 {
+                    binder::bind_all((min, max)).unwrap()
+                }
+                else {
+                    let (hi, lo) = binder::bind_all((max, min)).unwrap();
+                    (lo, hi)
+                };
+                *lo -= 1;
+                *hi += 1;
+                // single binds get in the way too
+                drop((lo, hi));
+                *max.bind_blocking().unwrap() += 1;
+            });
+        }
+    });
+    assert_eq!(*min.bind(), -2000);
+    assert_eq!(*max.bind(), 4000);
+}
+
+#[test]
+fn computed_values() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let w = Property::new(2.0f32);
+    let h = Property::new(3.0f32);
+    let calls = AtomicUsize::new(0);
+    let area = binder::computed((&w, &h), |w, h| {
+        calls.fetch_add(1, Ordering::Relaxed);
+        w * h
+    });
+    assert_eq!(*area.bind_ref(), 6.0);
+    assert_eq!(*area.bind_ref(), 6.0);
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+    // reading sources doesn't invalidate the value
+    let _ = *w.bind_ref();
+    let _ = *w.bind();
+    assert!(!area.is_dirty());
+
+    *w.bind() = 4.0;
+    *h.bind() = 0.5;
+    assert!(area.is_dirty());
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    assert_eq!(*area.bind_ref(), 2.0);
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+
+    let label = binder::computed(&w, |w| format!("width: {}", w));
+    assert_eq!(label.bind_ref().as_str(), "width: 4");
+}
+
+#[test]
+fn computed_stale_while_read() {
+    let x = Property::new(1i32);
+    let double = binder::Computed::new(&x, |x| x * 2);
+    let r = double.bind_ref();
+    *x.bind() = 5;
+    // can't replace the value while it's being read
+    assert_eq!(*double.bind_ref(), 2);
+    drop(r);
+    assert_eq!(*double.bind_ref(), 10);
+
+    // or while a source is bound mutably
+    *x.bind() = 6;
+    let b = x.bind();
+    assert_eq!(double.try_bind_ref().map(|v| *v), Ok(10));
+    assert!(double.is_dirty());
+    drop(b);
+    assert_eq!(*double.bind_ref(), 12);
+
+    // a panicking closure doesn't leave the value unusable
+    let fail = std::sync::atomic::AtomicBool::new(false);
+    let checked = binder::Computed::new(&x, |x| {
+        assert!(!fail.load(std::sync::atomic::Ordering::Relaxed));
+        x + 1
+    });
+    fail.store(true, std::sync::atomic::Ordering::Relaxed);
+    *x.bind() = 7;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *checked.bind_ref()));
+    assert!(result.is_err());
+    fail.store(false, std::sync::atomic::Ordering::Relaxed);
+    assert_eq!(*checked.bind_ref(), 8);
+}
+
+#[test]
+fn computed_waits_for_recompute() {
+    use std::sync::mpsc;
+    let x = Property::new(1i32);
+    let (started_tx, started) = mpsc::channel();
+    let (go, go_rx) = mpsc::channel::<()>();
+    let (started_tx, go_rx) = (std::sync::Mutex::new(started_tx), std::sync::Mutex::new(go_rx));
+    let slow = binder::Computed::new(&x, |x| {
+        if *x == 2 {
+            started_tx.lock().unwrap().send(()).unwrap();
+            go_rx.lock().unwrap().recv().unwrap();
+        }
+        x * 10
+    });
+    x.set(2);
+    std::thread::scope(|s| {
+        let first = s.spawn(|| *slow.bind_ref());
+        started.recv().unwrap();
+        // the value is bound mutably while it's recomputed, so this has to wait for it
+        let second = s.spawn(|| *slow.bind_ref());
+        assert!(!second.is_finished());
+        go.send(()).unwrap();
+        assert_eq!((first.join().unwrap(), second.join().unwrap()), (20, 20));
+    });
+}
+
+#[test]
+fn computed_contention() {
+    let x = Property::new(0usize);
+    let next = binder::computed(&x, |x| x + 1);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| for _ in 0..1000 {
+                assert!(*next.bind_ref() >= 1);
+            });
+        }
+        s.spawn(|| for i in 1..=1000 {
+            x.set(i);
+        });
+    });
+    assert_eq!(*next.bind_ref(), 1001);
+}
+
+#[test]
+fn linked_properties_concurrent() {
+    use std::sync::Arc;
+    let slow = |_: &i32| {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        Ok(())
+    };
+    for _ in 0..50 {
+        let a = Arc::new(Property::new(0).with_validator(slow));
+        let b = Arc::new(Property::new(0).with_validator(slow));
+        let _link = binder::link(&a, &b).unwrap();
+        std::thread::scope(|s| {
+            s.spawn(|| a.set(1));
+            s.spawn(|| b.set(2));
+        });
+        assert_eq!(a.get(), b.get());
+    }
+
+    // observers can link and unlink properties while a link is being made
+    let (b, c, d) = (Arc::new(Property::new(0)), Arc::new(Property::new(0)), Arc::new(Property::new(0)));
+    let links = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (c2, d2, made) = (c.clone(), d.clone(), links.clone());
+    let _sub = b.subscribe(move |_, _| {
+        let mut made = made.lock().unwrap();
+        made.clear();
+        made.push(binder::link(&c2, &d2).unwrap());
+    });
+    let _link = binder::link(&Arc::new(Property::new(5)), &b).unwrap();
+    assert_eq!(links.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn linked_properties() {
+    use std::sync::Arc;
+    let a = Arc::new(Property::new(1.0f32));
+    let b = Arc::new(Property::new(2.0f32));
+    let link = binder::link(&a, &b).unwrap();
+    assert_eq!(*b.bind_ref(), 1.0);
+
+    *a.bind() = 3.0;
+    assert_eq!(*b.bind_ref(), 3.0);
+    *b.bind() = 4.0;
+    assert_eq!(*a.bind_ref(), 4.0);
+
+    drop(link);
+    *a.bind() = 5.0;
+    assert_eq!(*b.bind_ref(), 4.0);
+}
+
+#[test]
+fn linked_chain() {
+    use std::sync::Arc;
+    use binder::LinkError;
+    let a = Arc::new(Property::new(0i32));
+    let b = Arc::new(Property::new(0i32));
+    let c = Arc::new(Property::new(0i32));
+    let changes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let _sub = {
+        let changes = changes.clone();
+        c.subscribe(move |_, _| { changes.fetch_add(1, std::sync::atomic::Ordering::Relaxed); })
+    };
+    let _ab = binder::link(&a, &b).unwrap();
+    let bc = binder::link(&b, &c).unwrap();
+
+    *a.bind() = 7;
+    assert_eq!(*c.bind_ref(), 7);
+    *c.bind() = 8;
+    assert_eq!(*a.bind_ref(), 8);
+    // no feedback: one change from the initial sync, one from a, one from c itself
+    assert_eq!(changes.load(std::sync::atomic::Ordering::Relaxed), 3);
+
+    assert_eq!(binder::link(&c, &a).unwrap_err(), LinkError::Cycle);
+    assert_eq!(binder::link(&a, &b).unwrap_err(), LinkError::Cycle);
+    assert_eq!(binder::link(&a, &a).unwrap_err(), LinkError::SameProperty);
+    drop(bc);
+    assert!(binder::link(&c, &a).is_ok());
+}
+
+#[test]
+fn linked_property_bound_elsewhere() {
+    use std::sync::Arc;
+    let a = Arc::new(Property::new(0i32));
+    let b = Arc::new(Property::new(0i32));
+    let _link = binder::link(&a, &b).unwrap();
+    let mut bound = b.bind();
+    *a.bind() = 1;
+    assert_eq!(*bound, 0);
+    *bound = 2;
+    drop(bound);
+    assert_eq!(*a.bind_ref(), 2);
+
+    // copied once the property is unbound, even by a read binding
+    let reading = b.bind_ref();
+    a.set(5);
+    assert_eq!(*reading, 2);
+    drop(reading);
+    assert_eq!(b.get(), 5);
+    let reading = std::thread::scope(|s| s.spawn(|| a.bind_ref()).join().unwrap());
+    b.set(6);
+    assert_eq!(*reading, 5);
+    std::thread::scope(|s| { s.spawn(move || drop(reading)); });
+    assert_eq!(a.get(), 6);
+
+    // a retry doesn't outlive the link
+    let reading = b.bind_ref();
+    a.set(7);
+    drop(_link);
+    drop(reading);
+    assert_eq!(b.get(), 6);
+}
+
+#[test]
+fn linked_property_rejects_value() {
+    use std::sync::Arc;
+    use binder::LinkError;
+    let a = Arc::new(Property::new(-1i32));
+    let b = Arc::new(Property::new(0i32).with_validator(|v| if *v >= 0 { Ok(()) } else { Err(String::from("negative")) }));
+    assert!(matches!(binder::link(&a, &b), Err(LinkError::Validation(e)) if e.message() == "negative"));
+    assert_eq!(b.get(), 0);
+    a.set(1);
+    let _link = binder::link(&a, &b).unwrap();
+    assert_eq!(b.get(), 1);
+}
+
+#[test]
+fn validators() {
+    use std::sync::{Arc, Mutex};
+    let p = Property::new(5i32)
+        .with_validator(|v| if *v >= 0 { Ok(()) } else { Err(String::from("negative")) })
+        .with_coercion(|v| *v = (*v).min(10));
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let _sub = {
+        let changes = changes.clone();
+        p.subscribe(move |old, new| changes.lock().unwrap().push((*old, *new)))
+    };
+
+    *p.bind() = -1;
+    assert_eq!(*p.bind_ref(), 5);
+    *p.bind() = 20;
+    assert_eq!(*p.bind_ref(), 10);
+    let mut b = p.bind();
+    *b = 7;
+    assert!(b.commit().is_ok());
+    let mut b = p.bind();
+    *b = -3;
+    assert_eq!(b.commit().unwrap_err().message(), "negative");
+    assert_eq!(*p.bind_ref(), 7);
+    // rejected changes aren't observed
+    assert_eq!(*changes.lock().unwrap(), vec![(5, 10), (10, 7)]);
+}
+
+#[test]
+fn range_validators() {
+    use binder::ValidationPolicy;
+    let clamped = Property::new(0.5f32).with_range(0.0..=1.0, ValidationPolicy::Coerce);
+    *clamped.bind() = -2.0;
+    assert_eq!(*clamped.bind_ref(), 0.0);
+    *clamped.bind() = 0.25;
+    assert_eq!(*clamped.bind_ref(), 0.25);
+
+    let strict = Property::new(3u8).with_range(1..=5, ValidationPolicy::Reject);
+    let mut b = strict.bind();
+    *b = 6;
+    assert_eq!(b.commit().unwrap_err().message(), "6 is outside of 1..=5");
+    assert_eq!(*strict.bind_ref(), 3);
+}
+
+#[test]
+fn range_validators_nan() {
+    use binder::ValidationPolicy;
+    let clamped = Property::new(0.5f32).with_range(0.0..=1.0, ValidationPolicy::Coerce);
+    clamped.set(f32::NAN);
+    assert_eq!(clamped.get(), 0.0);
+
+    let strict = Property::new(0.5f32).with_range(0.0..=1.0, ValidationPolicy::Reject);
+    let mut b = strict.bind();
+    *b = f32::NAN;
+    assert_eq!(b.commit().unwrap_err().message(), "NaN is outside of 0.0..=1.0");
+    assert_eq!(strict.get(), 0.5);
+}
+
+#[test]
+fn undo_redo() {
+    use std::sync::{Arc, Mutex};
+    use binder::UndoStack;
+    let undo = UndoStack::new();
+    let x = Arc::new(Property::new(0i32));
+    let y = Arc::new(Property::new(String::from("a")));
+    let _tracking = [undo.track(&x), undo.track(&y)];
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let _sub = {
+        let seen = seen.clone();
+        x.subscribe(move |_, new| seen.lock().unwrap().push(*new))
+    };
+
+    *x.bind() = 1;
+    {
+        let _outer = undo.group("Edit");
+        *x.bind() = 2;
+        let _inner = undo.group("Inner");
+        *y.bind() = String::from("b");
+    }
+    assert_eq!(undo.undo_name().as_deref(), Some("Edit"));
+    assert_eq!(undo.undo(), Ok(true));
+    assert_eq!((*x.bind_ref(), y.bind_ref().as_str()), (1, "a"));
+    assert_eq!(undo.redo_name().as_deref(), Some("Edit"));
+    assert_eq!(undo.undo(), Ok(true));
+    assert_eq!(*x.bind_ref(), 0);
+    assert_eq!(undo.undo(), Ok(false));
+    assert_eq!(undo.redo(), Ok(true));
+    assert_eq!(undo.redo(), Ok(true));
+    assert_eq!((*x.bind_ref(), y.bind_ref().as_str()), (2, "b"));
+    // replayed changes go through the normal bind path, but aren't recorded again
+    assert_eq!(*seen.lock().unwrap(), vec![1, 2, 1, 0, 1, 2]);
+    assert!(!undo.can_redo());
+
+    // a property that can't be bound leaves the whole entry alone
+    let held = y.bind();
+    assert!(matches!(undo.undo(), Err(binder::UndoError::Bind(binder::BindError::WouldDeadlock(_)))));
+    drop(held);
+    assert_eq!(*x.bind_ref(), 2);
+    assert!(undo.can_undo());
+
+    // a new change forgets everything that could be redone
+    assert_eq!(undo.undo(), Ok(true));
+    *x.bind() = 5;
+    assert!(!undo.can_redo());
+}
+
+#[test]
+fn undo_rejected() {
+    use std::sync::Arc;
+    use binder::{UndoError, UndoStack};
+    let undo = UndoStack::new();
+    let limit = Arc::new(std::sync::atomic::AtomicI32::new(i32::MAX));
+    let check = limit.clone();
+    let p = Arc::new(Property::new(0i32).with_validator(move |v| {
+        if *v <= check.load(std::sync::atomic::Ordering::Relaxed) { Ok(()) } else { Err(String::from("too big")) }
+    }));
+    let q = Arc::new(Property::new(0i32));
+    let _tracking = [undo.track(&p), undo.track(&q)];
+    p.set(5);
+    {
+        let _group = undo.group("Both");
+        p.set(1);
+        q.set(1);
+    }
+    undo.undo().unwrap();
+    limit.store(1, std::sync::atomic::Ordering::Relaxed);
+    // redoing `p` is fine, undoing to 5 isn't
+    assert_eq!(undo.redo(), Ok(true));
+    assert!(matches!(undo.undo(), Err(UndoError::Validation(e)) if e.message() == "too big"));
+    // the group's other change was reverted again, and the entry is still there
+    assert_eq!((p.get(), q.get()), (1, 1));
+    assert_eq!(undo.undo_name().as_deref(), Some("Both"));
+    limit.store(i32::MAX, std::sync::atomic::Ordering::Relaxed);
+    assert_eq!(undo.undo(), Ok(true));
+    assert_eq!((p.get(), q.get()), (5, 0));
+}
+
+#[test]
+fn undo_groups_per_thread() {
+    use std::sync::Arc;
+    let undo = binder::UndoStack::new();
+    let p = Arc::new(Property::new(0i32));
+    let _tracking = undo.track(&p);
+    let group = undo.group("Main");
+    p.set(1);
+    // not part of this thread's group
+    std::thread::scope(|s| { s.spawn(|| p.set(2)); });
+    assert!(undo.can_undo());
+    p.set(3);
+    drop(group);
+    assert_eq!(undo.undo_name().as_deref(), Some("Main"));
+    undo.undo().unwrap();
+    assert_eq!(p.get(), 0);
+    // the other thread's change is undone on its own
+    assert_eq!(undo.undo_name(), None);
+    undo.undo().unwrap();
+    assert_eq!(p.get(), 1);
+}
+
+#[test]
+fn undo_limits() {
+    use std::sync::Arc;
+    use binder::UndoStack;
+    let p = Arc::new(Property::new(0u64));
+    let undo = UndoStack::new().with_max_depth(3);
+    let _tracking = undo.track(&p);
+    for i in 1..=5 {
+        *p.bind() = i;
+    }
+    while undo.undo().unwrap() {}
+    assert_eq!(*p.bind_ref(), 2);
+
+    let undo = UndoStack::new().with_max_memory(1);
+    let _tracking = undo.track(&p);
+    *p.bind() = 10;
+    assert!(!undo.can_undo());
+}
+
+#[test]
+fn transactions() {
+    use std::sync::{Arc, Mutex};
+    use binder::Transaction;
+    let a = Property::new(1i32);
+    let b = Property::new(String::from("x")).with_validator(|s| if s.is_empty() { Err(String::from("empty")) } else { Ok(()) });
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let _sub = {
+        let seen = seen.clone();
+        a.subscribe(move |old, new| seen.lock().unwrap().push((*old, *new)))
+    };
+
+    // dropped without committing
+    {
+        let mut tx = Transaction::new();
+        tx.set(&a, 2).unwrap();
+        tx.update(&b, |s| s.push('y')).unwrap();
+        assert!(a.try_bind().is_err());
+    }
+    assert_eq!((*a.bind_ref(), b.bind_ref().as_str()), (1, "x"));
+
+    // several changes to one property are observed once
+    let mut tx = Transaction::new();
+    tx.set(&a, 2).unwrap();
+    tx.update(&a, |v| *v += 1).unwrap();
+    tx.update(&b, |s| s.push('y')).unwrap();
+    assert_eq!(tx.len(), 2);
+    assert_eq!(tx.commit(), Ok(()));
+    assert_eq!((*a.bind_ref(), b.bind_ref().as_str()), (3, "xy"));
+
+    // one rejected value rolls back everything
+    let mut tx = Transaction::new();
+    tx.set(&a, 4).unwrap();
+    tx.set(&b, String::new()).unwrap();
+    assert_eq!(tx.commit().unwrap_err().message(), "empty");
+    assert_eq!((*a.bind_ref(), b.bind_ref().as_str()), (3, "xy"));
+    assert_eq!(*seen.lock().unwrap(), vec![(1, 3)]);
+
+    // failing to bind leaves the transaction usable
+    let held = b.bind();
+    let mut tx = Transaction::new();
+    tx.set(&a, 5).unwrap();
+    assert!(tx.set(&b, String::from("z")).is_err());
+    drop(held);
+    tx.set(&b, String::from("z")).unwrap();
+    tx.commit().unwrap();
+    assert_eq!((*a.bind_ref(), b.bind_ref().as_str()), (5, "z"));
+}
+
+#[test]
+fn transaction_panic() {
+    use binder::Transaction;
+    let a = Property::new(1i32);
+    let b = Property::new(vec![1]);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut tx = Transaction::new();
+        tx.set(&a, 2).unwrap();
+        tx.update(&b, |v| {
+            v.push(2);
+            panic!("oops");
+        }).unwrap();
+    }));
+    assert!(result.is_err());
+    // rolled back, so there's nothing half-modified to poison
+    assert!(!a.is_poisoned() && !b.is_poisoned());
+    assert_eq!((a.get(), b.get()), (1, vec![1]));
+    a.set(3);
+    assert_eq!(a.get(), 3);
+}
+
+#[test]
+fn reflection() {
+    use std::sync::{Arc, Mutex};
+    use binder::{AnyProperty, FieldInfo, PropertyVisitor, Reflect};
+
+    struct Light {
+        intensity: Property<f32>,
+        name: Property<String>,
+    }
+
+    impl Reflect for Light {
+        fn visit<'a>(&'a self, visitor: &mut dyn PropertyVisitor<'a>) {
+            visitor.visit(&FieldInfo::new("intensity", "", Some((0.0, 1.0))), &self.intensity);
+            visitor.visit(&FieldInfo::new("name", "", None), &self.name);
+        }
+    }
+
+    struct Describe(Vec<String>);
+
+    impl<'a> PropertyVisitor<'a> for Describe {
+        fn visit(&mut self, info: &FieldInfo, property: &'a dyn AnyProperty) {
+            self.0.push(format!("{}: {} {:?}", info.name(), property.type_name(), info.range()));
+        }
+    }
+
+    let light = Light { intensity: Property::new(0.5), name: Property::new(String::from("sun")) };
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let _sub = {
+        let changes = changes.clone();
+        light.intensity.subscribe(move |old, new| changes.lock().unwrap().push((*old, *new)))
+    };
+
+    let mut describe = Describe(Vec::new());
+    light.visit(&mut describe);
+    assert_eq!(describe.0, [
+        String::from("intensity: f32 Some(0.0..=1.0)"),
+        format!("name: {} None", std::any::type_name::<String>()),
+    ]);
+
+    let intensity = light.property("intensity").unwrap();
+    assert_eq!(intensity.value_type_id(), std::any::TypeId::of::<f32>());
+    assert!(light.property("color").is_none());
+    {
+        let mut binding = intensity.bind_any();
+        assert!(binding.downcast_ref::<String>().is_none());
+        *binding.downcast_mut::<f32>().unwrap() = 1.0;
+        assert!(intensity.try_bind_ref_any().is_err());
+    }
+    assert_eq!(*changes.lock().unwrap(), vec![(0.5, 1.0)]);
+
+    let name = light.property("name").unwrap().bind_ref_any();
+    assert_eq!(name.downcast_ref::<String>().unwrap(), "sun");
+    assert!(light.property("name").unwrap().try_bind_any().is_err());
+}
+
+#[test]
+fn property_tree() {
+    use std::sync::Arc;
+    use binder::{PropertyTree, TreeError};
+    let tree = PropertyTree::new();
+    let scale = Arc::new(Property::new(1.0f32));
+    tree.register("render.scale", &scale).unwrap();
+    tree.register("render.shadows.quality", &Arc::new(Property::new(2u32))).unwrap();
+    tree.register("renderer.name", &Arc::new(Property::new(String::from("gl")))).unwrap();
+    tree.register("audio.volume", &Arc::new(Property::new(0.8f32))).unwrap();
+
+    assert_eq!(tree.register("render.scale", &scale), Err(TreeError::AlreadyRegistered(String::from("render.scale"))));
+    for path in ["", "render.", ".render", "render..scale", "render scale"] {
+        assert_eq!(tree.register(path, &scale), Err(TreeError::InvalidPath(String::from(path))));
+    }
+    assert_eq!(tree.len(), 4);
+
+    assert_eq!(tree.paths("render"), ["render.scale", "render.shadows.quality"]);
+    assert_eq!(tree.paths("render.scale"), ["render.scale"]);
+    assert_eq!(tree.paths("rend"), Vec::<String>::new());
+    assert_eq!(tree.paths("").len(), 4);
+
+    assert!(Arc::ptr_eq(&tree.get::<f32>("render.scale").unwrap(), &scale));
+    assert_eq!(tree.get::<f32>("render.size").unwrap_err(), TreeError::NotFound(String::from("render.size")));
+    assert_eq!(tree.get::<f64>("render.scale").unwrap_err(), TreeError::TypeMismatch {
+        path: String::from("render.scale"), expected: "f64", found: "f32"
+    });
+    assert_eq!(tree.get_any("render.shadows.quality").unwrap().type_name(), "u32");
+
+    {
+        let mut binding = tree.try_bind::<f32>("render.scale").unwrap();
+        *binding = 2.0;
+        assert!(matches!(tree.try_bind::<f32>("render.scale"), Err(TreeError::Bind(_))));
+    }
+    assert_eq!(*scale.bind_ref(), 2.0);
+
+    assert!(tree.unregister("render.scale"));
+    assert!(!tree.unregister("render.scale"));
+    assert!(!tree.contains("render.scale"));
+    assert_eq!(Arc::strong_count(&scale), 1);
+}
+
+#[test]
+fn save_and_load() {
+    use std::sync::Arc;
+    use binder::{LoadErrorKind, PropertyTree};
+    let tree = PropertyTree::new();
+    let volume = Arc::new(Property::new(0.5f32).with_validator(|v| {
+        if (0.0..=1.0).contains(v) { Ok(()) } else { Err(String::from("out of range")) }
+    }));
+    let name = Arc::new(Property::new(String::from("say \"hi\"\n\tback\\slash")));
+    let count = Arc::new(Property::new(3u8));
+    let secret = Arc::new(Property::new(7u8));
+    tree.register_persistent("audio.volume", &volume).unwrap();
+    tree.register_persistent("player.name", &name).unwrap();
+    tree.register_persistent("player.count", &count).unwrap();
+    tree.register("player.secret", &secret).unwrap();
+
+    let text = tree.save().unwrap();
+    assert_eq!(text, "audio.volume = 0.5\nplayer.count = 3\nplayer.name = \"say \\\"hi\\\"\\n\\tback\\\\slash\"\n");
+    *name.bind() = String::new();
+    tree.load(&text).unwrap();
+    assert_eq!(name.bind_ref().as_str(), "say \"hi\"\n\tback\\slash");
+
+    let errors = tree.load("# settings\n\naudio.volume = 2.0\nplayer.count=  12 \nplayer.secret = 1\nplayer.name = \"open\nnonsense\nplayer.missing = 1\nplayer.count = many\nplayer.name = unquoted text\n").unwrap_err();
+    assert_eq!(errors[1].line(), 5);
+    assert_eq!(errors[1].path(), Some("player.secret"));
+    assert_eq!(errors[1].kind(), &LoadErrorKind::UnknownKey);
+    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(errors, [
+        "line 3 ('audio.volume'): invalid value: out of range",
+        "line 5 ('player.secret'): no persistent property registered at this path",
+        "line 6 ('player.name'): syntax error: unterminated string",
+        "line 7: syntax error: expected `path = value`",
+        "line 8 ('player.missing'): no persistent property registered at this path",
+        "line 9 ('player.count'): couldn't parse value: invalid digit found in string",
+    ]);
+    // the lines that were fine were still applied
+    assert_eq!(*volume.bind_ref(), 0.5);
+    assert_eq!(*count.bind_ref(), 12);
+    assert_eq!(name.bind_ref().as_str(), "unquoted text");
+    assert_eq!(*secret.bind_ref(), 7);
+
+    let _held = count.bind_ref();
+    let errors = tree.load("player.count = 1").unwrap_err();
+    assert!(matches!(errors[0].kind(), LoadErrorKind::Bind(_)));
+
+    // observers run after the tree is unlocked, so they can register more properties
+    let tree = Arc::new(PropertyTree::new());
+    let level = Arc::new(Property::new(1u8));
+    tree.register_persistent("game.level", &level).unwrap();
+    let _subscription = level.subscribe({
+        let tree = tree.clone();
+        move |_, level| {
+            let path = format!("game.level{}.score", level);
+            tree.register_persistent(path, &Arc::new(Property::new(0u32))).unwrap();
+        }
+    });
+    tree.load("game.level = 2").unwrap();
+    assert_eq!(tree.len(), 2);
+    tree.load("game.level2.score = 5").unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let properties = vec![Property::new(1.5f32), Property::new(-2.0)];
+    assert_eq!(serde_json::to_string(&properties).unwrap(), "[1.5,-2.0]");
+    let _held = properties[1].bind();
+    let error = serde_json::to_string(&properties).unwrap_err().to_string();
+    assert!(error.starts_with("can't serialize Property<f32>: property is already bound"), "{}", error);
+
+    let properties: Vec<Property<String>> = serde_json::from_str(r#"["a", "b"]"#).unwrap();
+    assert_eq!(properties[1].bind_ref().as_str(), "b");
+    assert!(properties[0].try_bind().is_ok());
+}
+
+#[test]
+fn static_property() {
+    static COUNTER: Property<u32> = Property::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| for _ in 0..100 { *COUNTER.bind_blocking().unwrap() += 1; });
+        }
+    });
+    assert_eq!(*COUNTER.bind_ref(), 400);
+}
+
+#[test]
+fn property_size() {
+    use std::mem::size_of;
+    // the lock's state and owner, and the hooks. Waiters and debug diagnostics are kept elsewhere.
+    assert!(size_of::<Property<u64>>() <= size_of::<u64>() + 4 * size_of::<usize>());
+}
+
+#[test]
+fn atomic_property() {
+    use binder::AtomicProperty;
+    let flag = AtomicProperty::new(false);
+    flag.set(true);
+    assert_eq!(flag.compare_exchange(false, false), Err(true));
+    assert_eq!(flag.compare_exchange(true, false), Ok(true));
+    assert!(!flag.get());
+
+    // compared with PartialEq, not bitwise
+    let x = AtomicProperty::new(-0.0f64);
+    assert_eq!(x.compare_exchange(0.0, 1.0), Ok(-0.0));
+    assert_eq!(x.update(|v| *v *= 3.0), 3.0);
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Color(u8, u16, f32);
+    let color = AtomicProperty::new(Color(1, 2, 3.0));
+    assert_eq!(color.compare_exchange(Color(1, 2, 3.0), Color(4, 5, 6.0)), Ok(Color(1, 2, 3.0)));
+    assert_eq!(color.compare_exchange(Color(1, 2, 3.0), Color(0, 0, 0.0)), Err(Color(4, 5, 6.0)));
+    {
+        let mut binding = color.bind();
+        binding.0 = 7;
+        assert_eq!(color.get().0, 4);
+    }
+    assert_eq!(color.get(), Color(7, 5, 6.0));
+    // closures run outside the seqlock, so they can read the property
+    assert_eq!(color.update(|c| c.0 = color.get().0 + 1).0, 8);
+    assert_eq!(color.compare_exchange(Color(8, 5, 6.0), color.get()), Ok(Color(8, 5, 6.0)));
+    assert_eq!(color.into_inner(), Color(8, 5, 6.0));
+
+    // widgets that only read don't write back
+    let n = AtomicProperty::new(1u32);
+    let binding = n.bind();
+    n.set(2);
+    drop(binding);
+    assert_eq!(n.get(), 2);
+}
+
+#[test]
+fn atomic_property_threads() {
+    use binder::AtomicProperty;
+    let count = AtomicProperty::new(0u64);
+    let wide = AtomicProperty::new([0u64; 4]);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| for _ in 0..1000 {
+                count.update(|c| *c += 1);
+                wide.update(|w| { let n = w[0] + 1; *w = [n; 4]; });
+            });
+            s.spawn(|| for _ in 0..1000 {
+                let w = wide.get();
+                assert!(w.iter().all(|&v| v == w[0]), "torn read: {:?}", w);
+            });
+        }
+    });
+    assert_eq!(count.get(), 4000);
+    assert_eq!(wide.get(), [4000; 4]);
+}
+
+#[test]
+fn local_property() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use binder::{BindError, LocalProperty};
+
+    let p = LocalProperty::new(String::from("a"))
+        .with_validator(|v| if v.is_empty() { Err(String::from("empty")) } else { Ok(()) });
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = seen.clone();
+    let sub = p.subscribe(move |old, new| log.borrow_mut().push(format!("{}->{}", old, new)));
+
+    {
+        let mut b = p.bind();
+        assert!(matches!(p.try_bind(), Err(BindError::AlreadyBound(_))));
+        assert!(p.try_bind_ref().is_err());
+        b.push('b');
+    }
+    let r1 = p.bind_ref();
+    let r2 = p.bind_ref();
+    assert!(p.try_bind().is_err());
+    assert_eq!((r1.as_str(), r2.as_str()), ("ab", "ab"));
+    drop((r1, r2));
+
+    let mut b = p.bind();
+    b.clear();
+    assert_eq!(b.commit().unwrap_err().message(), "empty");
+    assert_eq!(*p.bind_ref(), "ab");
+    drop(sub);
+    *p.bind() = String::from("c");
+    assert_eq!(*seen.borrow(), ["a->ab"]);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _b = p.bind();
+        panic!();
+    }));
+    assert!(result.is_err());
+    assert!(matches!(p.try_bind_ref(), Err(BindError::Poisoned)));
+    p.clear_poison();
+
+    // a panicking observer leaves the property unbound, but poisoned
+    let sub = p.subscribe(|_, new| if new == "d" { panic!("observer") });
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *p.bind() = String::from("d"))).is_err());
+    assert_eq!(p.try_bind().unwrap_err(), BindError::Poisoned);
+    p.clear_poison();
+    drop(sub);
+    assert_eq!(p.into_inner(), "d");
+
+    let p = LocalProperty::new(1i32).with_validator(|v| if *v < 0 { panic!("validator") } else { Ok(()) });
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| *p.bind() = -1)).is_err());
+    assert!(p.is_poisoned());
+    p.clear_poison();
+    assert!(p.try_bind().is_ok());
+}
+
+#[test]
+fn bindable() {
+    use binder::{BindError, Bindable, LocalProperty};
+
+    fn toggle<P: Bindable<bool>>(p: &P) -> Result<bool, BindError> {
+        let mut b = p.try_bind()?;
+        *b = !*b;
+        Ok(*b)
+    }
+
+    let shared = Property::new(false);
+    let local = LocalProperty::new(true);
+    assert_eq!(toggle(&shared), Ok(true));
+    assert_eq!(toggle(&local), Ok(false));
+    let _r = Bindable::bind_ref(&local);
+    assert!(toggle(&local).is_err());
+}
+
+/// ```compile_fail
+/// fn sync<T: Sync>(_: &T) {}
+/// sync(&binder::LocalProperty::new(1f32));
+/// ```
+struct _DoctestLocalNotSync;
+
+#[test]
+fn value_operations() {
+    let p = Property::new(String::from("a"));
+    let changes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = changes.clone();
+    let _sub = p.subscribe(move |_, _| { counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst); });
+
+    p.set(String::from("b"));
+    assert_eq!(p.get(), "b");
+    assert_eq!(p.replace(String::from("c")), "b");
+    assert_eq!(p.update(|v| { v.push('d'); v.len() }), 2);
+    assert_eq!(p.take(), "cd");
+    assert_eq!(p.get(), "");
+    assert_eq!(changes.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+    let q = Property::new(String::from("q"));
+    p.swap(&q);
+    p.swap(&p);
+    assert_eq!((p.get(), q.get()), (String::from("q"), String::new()));
+
+    let b = p.bind();
+    assert!(p.try_get().is_err());
+    assert!(p.try_set(String::new()).is_err());
+    assert!(p.try_replace(String::new()).is_err());
+    assert!(p.try_take().is_err());
+    assert!(p.try_update(|_| panic!("shouldn't be called")).is_err());
+    assert!(q.try_swap(&p).is_err());
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| q.swap(&p)));
+    assert!(result.is_err());
+    assert!(!q.is_poisoned());
+    assert_eq!(q.try_get().unwrap(), "");
+    drop(b);
+    assert_eq!(q.try_swap(&p), Ok(()));
+    assert_eq!(q.try_update(|v| v.clone()), Ok(String::from("q")));
+
+    // a failed swap doesn't commit anything to the property that could be bound
+    let undo = binder::UndoStack::new();
+    let (a, b) = (std::sync::Arc::new(Property::new(1)), Property::new(2));
+    let _tracked = undo.track(&a);
+    let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let log = seen.clone();
+    let _sub = a.subscribe(move |old, new| log.lock().unwrap().push((*old, *new)));
+    let held = b.bind_ref();
+    assert!(a.try_swap(&b).is_err());
+    drop(held);
+    assert!(seen.lock().unwrap().is_empty());
+    assert!(!undo.can_undo());
+    a.swap(&b);
+    assert_eq!(*seen.lock().unwrap(), [(1, 2)]);
+}
+
+#[test]
+fn ownership() {
+    #[derive(Default)]
+    struct Settings {
+        name: Property<String>,
+        volume: Property<f32>,
+    }
+
+    let mut settings = Settings { volume: 0.5.into(), ..Default::default() };
+    settings.name.get_mut().push_str("main");
+    assert_eq!((settings.name.get(), settings.volume.get()), (String::from("main"), 0.5));
+
+    let copy = settings.name.clone();
+    copy.set(String::from("copy"));
+    assert_eq!((settings.name.get(), copy.into_inner()), (String::from("main"), String::from("copy")));
+
+    // `get_mut` doesn't go through the lock, so it works on poisoned properties too
+    let mut p = Property::new(1);
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _b = p.bind();
+        panic!();
+    }));
+    assert!(p.is_poisoned());
+    *p.get_mut() = 2;
+    p.clear_poison();
+    assert_eq!(p.get(), 2);
+}
+
+#[test]
+fn clone_bound_property() {
+    use binder::BindError;
+    let p = Property::new(1);
+    let mut binding = p.bind();
+    assert!(matches!(p.try_clone(), Err(BindError::WouldDeadlock(_))));
+    std::thread::scope(|s| {
+        // waits for the binding, so it can only ever see the value it's left with
+        let clone = s.spawn(|| p.clone());
+        *binding = 2;
+        drop(binding);
+        assert_eq!(clone.join().unwrap().into_inner(), 2);
+    });
+
+    let binding = p.bind();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| p.clone()));
+    assert!(result.is_err());
+    drop(binding);
+    assert_eq!(p.try_clone().map(Property::into_inner), Ok(2));
+}